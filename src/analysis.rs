@@ -0,0 +1,300 @@
+use lyon::geom::LineSegment;
+use lyon_path::math::Point;
+use lyon_path::{Path, PathEvent};
+use lyon_path::iterator::PathIterator;
+use rustfft::num_complex::Complex;
+use std::f32::consts::PI;
+
+use crate::error::Error;
+use crate::fft_drawer::{sample_series, select_draw_data_by, DrawData, Selection};
+use crate::path_util::{path_to_fft, sample_path};
+
+// How far the curve drawn by the waves is from the original path, in its units
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ReconstructionError {
+    // Distances between the points drawn and sampled at the same times
+    pub max: f32,
+    pub mean: f32,
+    pub rms: f32,
+    // Farthest any of the two curves goes from the other
+    pub hausdorff: f32,
+}
+
+// Compare the curve drawn by the waves with the original path, both taken at n_point times
+pub fn reconstruction_error(waves: &[DrawData], original: &Path, n_point: usize) -> Result<ReconstructionError, Error> {
+    let samples: Vec<Point> = sample_path(original, n_point)?.iter()
+        .map(|c| Point::new(c.re, c.im))
+        .collect();
+    let drawn = sample_series(waves, n_point);
+
+    let distances: Vec<f32> = samples.iter().zip(&drawn).map(|(s, d)| (*s - *d).length()).collect();
+    let max = distances.iter().cloned().fold(0.0, f32::max);
+    let mean = distances.iter().sum::<f32>() / n_point as f32;
+    let rms = (distances.iter().map(|d| d * d).sum::<f32>() / n_point as f32).sqrt();
+
+    // The drawn curve is closed, the original one is flattened as it is
+    let mut drawn_segments: Vec<LineSegment<f32>> = drawn.windows(2)
+        .map(|p| LineSegment { from: p[0], to: p[1] })
+        .collect();
+    drawn_segments.push(LineSegment { from: drawn[n_point - 1], to: drawn[0] });
+    let original_segments = flattened_segments(original);
+    let hausdorff = farthest_distance(&drawn, &original_segments)
+        .max(farthest_distance(&samples, &drawn_segments));
+
+    Ok(ReconstructionError {
+        max,
+        mean,
+        rms,
+        hausdorff,
+    })
+}
+
+// What the drawing should reach, with as few waves as possible
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WaveTarget {
+    // Share of the energy of the rotating waves, from 0 to 1
+    Energy(f32),
+    // Largest distance to the sampled points, in path units
+    MaxError(f32),
+}
+
+// Smallest number of waves meeting the target, taken in the drawing order
+pub fn choose_wave_count(path: &Path, num_sample: usize, target: WaveTarget, selection: Selection) -> Result<usize, Error> {
+    match target {
+        WaveTarget::Energy(share) if !(share > 0.0 && share <= 1.0) => {
+            return Err(Error::InvalidTarget(format!("energy {}, expected a share in (0, 1]", share)));
+        }
+        WaveTarget::MaxError(max_error) if !(max_error > 0.0 && max_error.is_finite()) => {
+            return Err(Error::InvalidTarget(format!("maximum error {}, expected a positive distance", max_error)));
+        }
+        _ => {}
+    }
+
+    let samples = sample_path(path, num_sample)?;
+    let waves = select_draw_data_by(&path_to_fft(path.clone(), num_sample)?, num_sample, selection);
+
+    match target {
+        WaveTarget::Energy(share) => {
+            // The first wave only moves the drawing, it holds no energy of the shape
+            let total: f32 = waves[1..].iter().map(|d| d.radius * d.radius).sum();
+            if total <= 0.0 {
+                return Ok(1);
+            }
+            let mut energy = 0.0;
+            for (i, d) in waves.iter().enumerate().skip(1) {
+                energy += d.radius * d.radius;
+                if energy >= share * total {
+                    return Ok(i + 1);
+                }
+            }
+            // All the waves hold all the energy, whatever the rounding
+            Ok(waves.len())
+        }
+        WaveTarget::MaxError(max_error) => {
+            // Add the waves one by one to the points drawn at the sample times
+            let mut drawn = vec![Complex::new(0.0, 0.0); num_sample];
+            let mut error = f32::INFINITY;
+            for (i, d) in waves.iter().enumerate() {
+                for (j, p) in drawn.iter_mut().enumerate() {
+                    // Whole turns are dropped to keep the precision
+                    let turn = (d.frequency as i64 * j as i64).rem_euclid(num_sample as i64) as f32 / num_sample as f32;
+                    *p += Complex::from_polar(d.radius, d.angle + 2.0 * PI * turn);
+                }
+                error = samples.iter().zip(&drawn).map(|(s, p)| (s - p).norm()).fold(0.0, f32::max);
+                if error <= max_error {
+                    return Ok(i + 1);
+                }
+            }
+            Err(Error::InvalidTarget(format!("maximum error {}, the {} waves still draw {} away", max_error, waves.len(), error)))
+        }
+    }
+}
+
+fn flattened_segments(path: &Path) -> Vec<LineSegment<f32>> {
+    let mut segments = Vec::new();
+    for evt in path.iter().flattened(0.01) {
+        match evt {
+            PathEvent::Line { from, to } => segments.push(LineSegment { from, to }),
+            PathEvent::End { last, first, close: true } => segments.push(LineSegment { from: last, to: first }),
+            _ => {}
+        }
+    }
+    segments
+}
+
+// Largest distance from one of the points to the closest segment
+fn farthest_distance(points: &[Point], segments: &[LineSegment<f32>]) -> f32 {
+    let grid = SegmentGrid::new(segments);
+    points.iter()
+        .map(|p| grid.closest_distance(*p))
+        .fold(0.0, f32::max)
+}
+
+// Square cells covering the segments, each listing the segments crossing it,
+// so that only the segments around a point are compared with it
+struct SegmentGrid<'a> {
+    segments: &'a [LineSegment<f32>],
+    origin: Point,
+    cell_size: f32,
+    columns: usize,
+    rows: usize,
+    cells: Vec<Vec<usize>>,
+}
+
+impl<'a> SegmentGrid<'a> {
+    fn new(segments: &'a [LineSegment<f32>]) -> SegmentGrid<'a> {
+        let (min, max) = segments.iter()
+            .flat_map(|s| [s.from, s.to])
+            .fold((Point::splat(f32::INFINITY), Point::splat(f32::NEG_INFINITY)), |(min, max), p| (min.min(p), max.max(p)));
+        if segments.is_empty() || !(min.x.is_finite() && min.y.is_finite() && max.x.is_finite() && max.y.is_finite()) {
+            // A single cell, compared with every segment
+            return SegmentGrid { segments, origin: Point::zero(), cell_size: f32::INFINITY, columns: 1, rows: 1, cells: vec![(0..segments.len()).collect()] };
+        }
+
+        // About as many cells as segments
+        let size = (max - min).x.max((max - min).y);
+        let cell_size = if size > 0.0 { size / (segments.len() as f32).sqrt().ceil() } else { 1.0 };
+        let columns = ((max.x - min.x) / cell_size) as usize + 1;
+        let rows = ((max.y - min.y) / cell_size) as usize + 1;
+        let mut grid = SegmentGrid { segments, origin: min, cell_size, columns, rows, cells: vec![Vec::new(); columns * rows] };
+        for (i, segment) in segments.iter().enumerate() {
+            let (from_column, from_row) = grid.cell(segment.from.min(segment.to));
+            let (to_column, to_row) = grid.cell(segment.from.max(segment.to));
+            for row in from_row..=to_row {
+                for column in from_column..=to_column {
+                    grid.cells[row * columns + column].push(i);
+                }
+            }
+        }
+        grid
+    }
+
+    // The cell holding the point, or the closest one when it is outside
+    fn cell(&self, p: Point) -> (usize, usize) {
+        let column = ((p.x - self.origin.x) / self.cell_size).max(0.0) as usize;
+        let row = ((p.y - self.origin.y) / self.cell_size).max(0.0) as usize;
+        (column.min(self.columns - 1), row.min(self.rows - 1))
+    }
+
+    // Searches the rings of cells around the point, until the segments left are all farther
+    // than the closest one found: the cells of a ring are at least (ring - 1) * cell_size away
+    fn closest_distance(&self, p: Point) -> f32 {
+        let (column, row) = self.cell(p);
+        let mut closest = f32::INFINITY;
+        for ring in 0..self.columns.max(self.rows) {
+            if ring > 0 && closest <= (ring - 1) as f32 * self.cell_size {
+                break;
+            }
+            let (first_row, last_row) = (row.saturating_sub(ring), (row + ring).min(self.rows - 1));
+            let (first_column, last_column) = (column.saturating_sub(ring), (column + ring).min(self.columns - 1));
+            for r in first_row..=last_row {
+                for c in first_column..=last_column {
+                    // Only the border of the ring, the inside was searched already
+                    if r.abs_diff(row) != ring && c.abs_diff(column) != ring {
+                        continue;
+                    }
+                    for &i in &self.cells[r * self.columns + c] {
+                        closest = closest.min(segment_distance(&self.segments[i], p));
+                    }
+                }
+            }
+        }
+        closest
+    }
+}
+
+fn segment_distance(segment: &LineSegment<f32>, p: Point) -> f32 {
+    let direction = segment.to - segment.from;
+    let length = direction.square_length();
+    if length == 0.0 {
+        return (p - segment.from).length();
+    }
+    let t = ((p - segment.from).dot(direction) / length).clamp(0.0, 1.0);
+    (p - segment.sample(t)).length()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::path_to_draw_data;
+    use crate::path_util::build_path_from_ellipse;
+
+    #[test]
+    fn measures_how_far_the_waves_draw() {
+        let circle = build_path_from_ellipse(0.0, 0.0, 10.0, 10.0).unwrap();
+        // A circle only needs a wave
+        let waves = path_to_draw_data(circle.clone(), 256, 3).unwrap();
+        let error = reconstruction_error(&waves, &circle, 256).unwrap();
+        assert!(error.max < 0.1);
+        assert!(error.hausdorff < 0.1);
+
+        // Without the rotating waves, everything is drawn at the center
+        let error = reconstruction_error(&waves[..1], &circle, 256).unwrap();
+        assert!((error.mean - 10.0).abs() < 0.1);
+        assert!((error.rms - 10.0).abs() < 0.1);
+        assert!((error.hausdorff - 10.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn chooses_the_fewest_waves_for_the_target() {
+        // All the energy of a circle is in a single wave
+        let circle = build_path_from_ellipse(0.0, 0.0, 10.0, 10.0).unwrap();
+        assert_eq!(choose_wave_count(&circle, 256, WaveTarget::Energy(0.999), Selection::LowestFrequency).unwrap(), 2);
+        assert_eq!(choose_wave_count(&circle, 256, WaveTarget::MaxError(0.1), Selection::LowestFrequency).unwrap(), 2);
+
+        // The corners of a square need many more
+        let square = crate::path_util::build_path_from_svg("M 0 0 L 10 0 L 10 10 L 0 10 Z").unwrap();
+        let coarse = choose_wave_count(&square, 256, WaveTarget::MaxError(1.0), Selection::LowestFrequency).unwrap();
+        let fine = choose_wave_count(&square, 256, WaveTarget::MaxError(0.1), Selection::LowestFrequency).unwrap();
+        assert!(2 < coarse && coarse < fine);
+        let waves = path_to_draw_data(square.clone(), 256, fine).unwrap();
+        assert!(reconstruction_error(&waves, &square, 256).unwrap().max <= 0.1);
+    }
+
+    #[test]
+    fn rejects_the_targets_which_cannot_be_met() {
+        let circle = build_path_from_ellipse(0.0, 0.0, 10.0, 10.0).unwrap();
+        for target in [WaveTarget::Energy(2.0), WaveTarget::Energy(-1.0), WaveTarget::MaxError(0.0), WaveTarget::MaxError(f32::NAN)] {
+            assert!(matches!(choose_wave_count(&circle, 64, target, Selection::LowestFrequency), Err(Error::InvalidTarget(_))));
+        }
+        // Even with every wave, the drawing is only as precise as the floats
+        let result = choose_wave_count(&circle, 64, WaveTarget::MaxError(1e-12), Selection::LowestFrequency);
+        assert!(matches!(result, Err(Error::InvalidTarget(_))));
+        assert!(choose_wave_count(&circle, 64, WaveTarget::Energy(1.0), Selection::LowestFrequency).is_ok());
+    }
+
+    #[test]
+    fn finds_the_closest_segments_around_each_point() {
+        let square = crate::path_util::build_path_from_svg("M 0 0 L 10 0 L 10 10 L 0 10 Z").unwrap();
+        let circle = build_path_from_ellipse(3.0, 4.0, 6.0, 2.0).unwrap();
+        for path in [square, circle] {
+            let segments = flattened_segments(&path);
+            // Points inside, on and far outside the segments
+            let points: Vec<Point> = (0..200)
+                .map(|i| Point::new((i % 20) as f32 * 1.7 - 12.0, (i / 20) as f32 * 3.1 - 9.0))
+                .collect();
+            for p in points {
+                let every_segment = segments.iter().map(|s| segment_distance(s, p)).fold(f32::INFINITY, f32::min);
+                assert_eq!(farthest_distance(&[p], &segments), every_segment);
+            }
+        }
+    }
+
+    #[test]
+    fn draws_within_the_target() {
+        let circle = build_path_from_ellipse(0.0, 0.0, 40.0, 40.0).unwrap();
+        let square = crate::path_util::build_path_from_svg("M 0 0 L 10 0 L 10 10 L 0 10 Z").unwrap();
+        for selection in [Selection::LowestFrequency, Selection::LargestMagnitude] {
+            let drawings = crate::paths_to_drawings_for_target(vec![circle.clone(), square.clone()], 256, WaveTarget::MaxError(0.5), selection).unwrap();
+            for drawing in &drawings {
+                let original = drawing.original.as_ref().unwrap();
+                assert!(reconstruction_error(&drawing.waves, original, 256).unwrap().max <= 0.5);
+            }
+
+            // The drawn waves hold the energy asked for
+            let drawings = crate::paths_to_drawings_for_target(vec![circle.clone()], 256, WaveTarget::Energy(0.999), selection).unwrap();
+            let original = drawings[0].original.as_ref().unwrap();
+            assert!(reconstruction_error(&drawings[0].waves, original, 256).unwrap().max < 1.0);
+        }
+    }
+}
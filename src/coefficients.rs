@@ -0,0 +1,168 @@
+use std::fs;
+
+use lyon_path::math::{rect, Rect};
+use serde::{Deserialize, Serialize};
+
+use crate::error::Error;
+use crate::fft_drawer::{DrawData, Drawing};
+use crate::path_util::{build_path_from_svg, path_to_svg};
+
+// Bumped whenever the layout of the coefficient files changes
+pub const COEFFICIENTS_VERSION: u32 = 1;
+
+// The waves of the drawings, saved to be drawn again without running the FFT:
+// {
+//   "version": 1,
+//   "bounds": { "x": 0, "y": 0, "width": 100, "height": 100 },
+//   "drawings": [{
+//     "sample_count": 10240,
+//     "wave_count": 201,
+//     "path_length": 412.5,
+//     "original": "M0 0 L100 0 ...",
+//     "waves": [{ "frequency": 0, "radius": 50.2, "angle": 0.78 }, ...]
+//   }]
+// }
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CoefficientsFile {
+    pub version: u32,
+    // Area of the source fitted to the canvas
+    pub bounds: Bounds,
+    pub drawings: Vec<DrawingCoefficients>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Bounds {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl From<Rect> for Bounds {
+    fn from(bounds: Rect) -> Bounds {
+        Bounds {
+            x: bounds.origin.x,
+            y: bounds.origin.y,
+            width: bounds.size.width,
+            height: bounds.size.height,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DrawingCoefficients {
+    pub sample_count: usize,
+    pub wave_count: usize,
+    pub path_length: f32,
+    // SVG path data of the original path, when known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub original: Option<String>,
+    // In the order they were selected, the first one not rotating
+    pub waves: Vec<DrawData>,
+}
+
+impl CoefficientsFile {
+    pub fn new(drawings: &[Drawing], bounds: Rect) -> CoefficientsFile {
+        CoefficientsFile {
+            version: COEFFICIENTS_VERSION,
+            bounds: Bounds::from(bounds),
+            drawings: drawings.iter()
+                .map(|drawing| DrawingCoefficients {
+                    sample_count: drawing.sample_count,
+                    wave_count: drawing.waves.len(),
+                    path_length: drawing.path_length,
+                    original: drawing.original.as_ref().map(path_to_svg),
+                    waves: drawing.waves.clone(),
+                })
+                .collect(),
+        }
+    }
+
+    pub fn from_json(content: &str) -> Result<CoefficientsFile, Error> {
+        let file: CoefficientsFile = serde_json::from_str(content)
+            .map_err(|err| Error::InvalidCoefficients(err.to_string()))?;
+        if file.version != COEFFICIENTS_VERSION {
+            return Err(Error::InvalidCoefficients(format!("unsupported version {}", file.version)));
+        }
+        if let Some(drawing) = file.drawings.iter().find(|d| d.wave_count != d.waves.len()) {
+            return Err(Error::InvalidCoefficients(format!("{} waves listed instead of {}", drawing.waves.len(), drawing.wave_count)));
+        }
+        if file.drawings.iter().any(|d| d.waves.is_empty()) {
+            return Err(Error::InvalidCoefficients("a drawing has no waves".to_string()));
+        }
+        if file.drawings.iter().any(|d| d.sample_count == 0) {
+            return Err(Error::InvalidCoefficients("a drawing was sampled at no point".to_string()));
+        }
+        Ok(file)
+    }
+
+    pub fn to_json(&self) -> String {
+        // Only made of numbers and strings, which always serialize
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    pub fn bounds(&self) -> Rect {
+        rect(self.bounds.x, self.bounds.y, self.bounds.width, self.bounds.height)
+    }
+
+    pub fn to_drawings(&self) -> Result<Vec<Drawing>, Error> {
+        self.drawings.iter()
+            .map(|coefficients| {
+                let original = match &coefficients.original {
+                    Some(data) => Some(build_path_from_svg(data)?),
+                    None => None,
+                };
+                let mut drawing = Drawing::new(coefficients.waves.clone(), original);
+                drawing.sample_count = coefficients.sample_count;
+                drawing.path_length = coefficients.path_length;
+                Ok(drawing)
+            })
+            .collect()
+    }
+}
+
+pub fn read_coefficients_file(file_name: &str) -> Result<CoefficientsFile, Error> {
+    let content = fs::read_to_string(file_name).map_err(|err| Error::io(file_name, err))?;
+    CoefficientsFile::from_json(&content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::paths_to_drawings;
+
+    #[test]
+    fn reads_back_the_drawings() {
+        let path = build_path_from_svg("M 0 0 L 10 0 L 10 10 Z").unwrap();
+        let drawings = paths_to_drawings(vec![path], 64, 5).unwrap();
+        let file = CoefficientsFile::new(&drawings, rect(0.0, 0.0, 10.0, 10.0));
+        let json = file.to_json();
+        assert!(json.contains("\"original\": \"M0 0 L10 0 L10 10 Z\""));
+
+        let file = CoefficientsFile::from_json(&json).unwrap();
+        assert_eq!(file.bounds(), rect(0.0, 0.0, 10.0, 10.0));
+        let read = file.to_drawings().unwrap();
+        assert_eq!(read[0].sample_count, 64);
+        assert_eq!(read[0].waves.len(), 5);
+        assert!((read[0].path_length - (20.0 + 200f32.sqrt())).abs() < 1e-3);
+        assert_eq!(read[0].waves[1].radius, drawings[0].waves[1].radius);
+    }
+
+    #[test]
+    fn rejects_other_versions() {
+        let json = "{\"version\": 2, \"bounds\": {\"x\": 0, \"y\": 0, \"width\": 1, \"height\": 1}, \"drawings\": []}";
+        assert!(matches!(CoefficientsFile::from_json(json), Err(Error::InvalidCoefficients(_))));
+        assert!(matches!(CoefficientsFile::from_json("[]"), Err(Error::InvalidCoefficients(_))));
+    }
+
+    #[test]
+    fn rejects_drawings_with_nothing_to_draw() {
+        let drawing = |sample_count: usize, waves: &str| format!(
+            "{{\"version\": 1, \"bounds\": {{\"x\": 0, \"y\": 0, \"width\": 1, \"height\": 1}}, \"drawings\": [{{\"sample_count\": {}, \"wave_count\": {}, \"path_length\": 1, \"waves\": [{}]}}]}}",
+            sample_count, if waves.is_empty() { 0 } else { 1 }, waves);
+        let wave = "{\"frequency\": 0, \"radius\": 1, \"angle\": 0}";
+        assert!(CoefficientsFile::from_json(&drawing(8, wave)).is_ok());
+        assert!(matches!(CoefficientsFile::from_json(&drawing(8, "")), Err(Error::InvalidCoefficients(_))));
+        assert!(matches!(CoefficientsFile::from_json(&drawing(0, wave)), Err(Error::InvalidCoefficients(_))));
+    }
+}
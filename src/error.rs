@@ -0,0 +1,83 @@
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug)]
+pub enum Error {
+    // The path data could not be parsed, position is the column of the faulty character,
+    // counted from 1, when it is known
+    InvalidPathData { position: Option<usize> },
+    // The SVG document itself could not be parsed
+    InvalidSvg(String),
+    // No path or shape to draw
+    MissingPath,
+    // The path is the file being read or written, when there is one
+    Io { path: Option<PathBuf>, source: io::Error },
+    // The path has no length, so it cannot be sampled
+    EmptyPath,
+    InvalidSampleCount(usize),
+    // The energy share or the maximum error to reach cannot be met
+    InvalidTarget(String),
+    // The period or the frame rate of the animations is not a positive number
+    InvalidTiming(String),
+    // The width, height or padding of the output canvas is not a number or out of range
+    InvalidCanvas(String),
+    // The GIF palette cannot hold this number of colors
+    InvalidPaletteSize(String),
+    UnknownVisualizer(String),
+    // The visualizer cannot write to this kind of output
+    UnsupportedOutput(String),
+    // The image could not be drawn or encoded
+    Render(String),
+    // The coefficient file could not be read
+    InvalidCoefficients(String),
+    InvalidColor(String),
+    // The theme file could not be read
+    InvalidTheme(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::InvalidPathData { position: Some(position) } => write!(f, "invalid path data at column {}", position),
+            Error::InvalidPathData { position: None } => write!(f, "invalid path data"),
+            Error::InvalidSvg(message) => write!(f, "invalid SVG document: {}", message),
+            Error::MissingPath => write!(f, "no path to draw"),
+            Error::Io { path: Some(path), source } => write!(f, "{}: {}", path.display(), source),
+            Error::Io { path: None, source } => write!(f, "{}", source),
+            Error::EmptyPath => write!(f, "the path is empty or has a zero length"),
+            Error::InvalidSampleCount(count) => write!(f, "cannot draw with {} sample points", count),
+            Error::InvalidTarget(target) => write!(f, "invalid target {}", target),
+            Error::InvalidTiming(timing) => write!(f, "invalid {}, expected a positive number", timing),
+            Error::InvalidCanvas(message) => write!(f, "invalid canvas {}", message),
+            Error::InvalidPaletteSize(size) => write!(f, "invalid palette size {}, expected from 64 to 256 colors", size),
+            Error::UnknownVisualizer(name) => write!(f, "unknown visualizer {}", name),
+            Error::UnsupportedOutput(message) => write!(f, "unsupported output: {}", message),
+            Error::Render(message) => write!(f, "cannot render: {}", message),
+            Error::InvalidCoefficients(message) => write!(f, "invalid coefficient file: {}", message),
+            Error::InvalidColor(color) => write!(f, "invalid color {}", color),
+            Error::InvalidTheme(message) => write!(f, "invalid theme: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl Error {
+    pub fn io<P: AsRef<Path>>(path: P, source: io::Error) -> Error {
+        Error::Io { path: Some(path.as_ref().to_path_buf()), source }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(source: io::Error) -> Error {
+        Error::Io { path: None, source }
+    }
+}
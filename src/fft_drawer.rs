@@ -1,6 +1,14 @@
+use std::f32::consts::PI;
+
+use lyon::algorithms::aabb::bounding_rect;
+use lyon_path::math::{point, vector, Point, Rect};
+use lyon_path::Path;
 use rustfft::num_complex::Complex;
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug)]
+use crate::path_util::compute_path_length;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct DrawData {
     pub frequency: f32,
     pub radius: f32,
@@ -25,3 +33,165 @@ impl DrawData {
         }
     }
 }
+
+// The waves drawing a path
+#[derive(Clone, Debug)]
+pub struct Drawing {
+    pub waves: Vec<DrawData>,
+    // The path the waves were computed from, when known
+    pub original: Option<Path>,
+    // Bounding box of the original path, in its units
+    pub bounds: Option<Rect>,
+    // Number of points sampled along the original path
+    pub sample_count: usize,
+    pub path_length: f32,
+}
+
+impl Drawing {
+    // Without more details, the waves are taken as all the samples there were
+    pub fn new(waves: Vec<DrawData>, original: Option<Path>) -> Drawing {
+        let path_length = original.as_ref().map(compute_path_length).unwrap_or(0.0);
+        Drawing {
+            sample_count: waves.len(),
+            waves,
+            bounds: original.as_ref().map(|path| bounding_rect(path.iter())),
+            original,
+            path_length,
+        }
+    }
+}
+
+// Which waves are kept when there are fewer than samples
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Selection {
+    // 0, 1, -1, 2, -2...
+    LowestFrequency,
+    // The largest circles first, for shapes with sharp features
+    LargestMagnitude,
+}
+
+// The first wave, which does not rotate, is always kept
+pub fn select_draw_data_by(fft_result: &[Complex<f32>], num_wave: usize, selection: Selection) -> Vec<DrawData> {
+    match selection {
+        Selection::LowestFrequency => select_draw_data(fft_result, num_wave),
+        Selection::LargestMagnitude => {
+            let fft_size = fft_result.len();
+            let mut indices: Vec<usize> = (1..fft_size).collect();
+            indices.sort_by(|&a, &b| fft_result[b].norm().total_cmp(&fft_result[a].norm()));
+
+            let mut data = vec![DrawData::new_from_complex(0.0, fft_result[0])];
+            for &i in indices.iter().take(num_wave.saturating_sub(1)) {
+                // Bins after the middle are the negative frequencies
+                let frequency = if i <= fft_size / 2 { i as f32 } else { i as f32 - fft_size as f32 };
+                data.push(DrawData::new_from_complex(frequency, fft_result[i]));
+            }
+            data
+        }
+    }
+}
+
+pub fn select_draw_data(fft_result: &[Complex<f32>], num_wave: usize) -> Vec<DrawData> {
+    let fft_size = fft_result.len();
+
+    let mut data = Vec::new();
+    data.push(DrawData::new_from_complex(0 as f32, fft_result[0]));
+    // Pair each positive frequency with its negative one, an even count ending on a positive one
+    let mut i = 1;
+    while data.len() < num_wave.min(fft_size) {
+        data.push(DrawData::new_from_complex(i as f32, fft_result[i]));
+        if data.len() < num_wave {
+            data.push(DrawData::new_from_complex(-(i as i32) as f32, fft_result[fft_size - i]));
+        }
+        i += 1;
+    }
+    data
+}
+
+// Position drawn by the waves at t, a full revolution going from 0 to 1
+pub fn series_point(data: &[DrawData], t: f32) -> Point {
+    let sum: Complex<f32> = data.iter()
+        .map(|d| Complex::from_polar(d.radius, d.angle + 2.0 * PI * d.frequency * t))
+        .sum();
+    point(sum.re, sum.im)
+}
+
+// Every point of the chain of waves at t, from the origin to the tip,
+// each wave turning around the end of the previous one
+pub fn epicycle_centers(data: &[DrawData], t: f32) -> Vec<Point> {
+    let mut centers = Vec::with_capacity(data.len() + 1);
+    let mut center = point(0.0, 0.0);
+    centers.push(center);
+    for d in data {
+        let c = Complex::from_polar(d.radius, d.angle + 2.0 * PI * d.frequency * t);
+        center += vector(c.re, c.im);
+        centers.push(center);
+    }
+    centers
+}
+
+// Positions drawn at n_point evenly spaced times of a revolution, starting at 0
+pub fn sample_series(data: &[DrawData], n_point: usize) -> Vec<Point> {
+    (0..n_point)
+        .map(|i| series_point(data, i as f32 / n_point as f32))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn alternates_positive_and_negative_frequencies() {
+        let fft_result: Vec<Complex<f32>> = (0..8).map(|i| Complex { re: i as f32, im: 0.0 }).collect();
+        let data = select_draw_data(&fft_result, 5);
+        let frequencies: Vec<f32> = data.iter().map(|d| d.frequency).collect();
+        assert_eq!(frequencies, vec![0.0, 1.0, -1.0, 2.0, -2.0]);
+        assert_eq!(data[2].radius, 7.0);
+
+        // An even count keeps the next positive frequency alone
+        let data = select_draw_data(&fft_result, 4);
+        let frequencies: Vec<f32> = data.iter().map(|d| d.frequency).collect();
+        assert_eq!(frequencies, vec![0.0, 1.0, -1.0, 2.0]);
+        assert_eq!(select_draw_data(&fft_result, 8).len(), 8);
+    }
+
+    #[test]
+    fn keeps_the_largest_waves() {
+        let radii = [9.0, 1.0, 0.0, 5.0, 3.0, 4.0, 0.5, 2.0];
+        let fft_result: Vec<Complex<f32>> = radii.iter().map(|&r| Complex { re: r, im: 0.0 }).collect();
+        let data = select_draw_data_by(&fft_result, 4, Selection::LargestMagnitude);
+        let frequencies: Vec<f32> = data.iter().map(|d| d.frequency).collect();
+        assert_eq!(frequencies, vec![0.0, 3.0, -3.0, 4.0]);
+    }
+
+    #[test]
+    fn goes_back_to_the_samples() {
+        let samples = vec![
+            Complex { re: 0.0, im: 0.0 },
+            Complex { re: 10.0, im: 0.0 },
+            Complex { re: 10.0, im: 10.0 },
+            Complex { re: 0.0, im: 10.0 },
+        ];
+        let mut fft_result = samples.clone();
+        rustfft::FftPlanner::new().plan_fft_forward(4).process(&mut fft_result);
+        let fft_result: Vec<Complex<f32>> = fft_result.iter().map(|c| c / 4.0).collect();
+
+        // With every frequency, the series goes through every sample
+        let mut data = select_draw_data(&fft_result, 3);
+        data.push(DrawData::new_from_complex(2.0, fft_result[2]));
+        for (sample, p) in samples.iter().zip(sample_series(&data, 4)) {
+            assert!((p - point(sample.re, sample.im)).length() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn chains_the_epicycles_up_to_the_tip() {
+        let data = vec![DrawData::new(0.0, 1.0, 0.0), DrawData::new(1.0, 2.0, 0.0), DrawData::new(-1.0, 3.0, 0.0)];
+        let centers = epicycle_centers(&data, 0.25);
+        assert_eq!(centers.len(), 4);
+        assert_eq!(centers[0], point(0.0, 0.0));
+        assert!((centers[1] - point(1.0, 0.0)).length() < 1e-5);
+        assert!((centers[2] - point(1.0, 2.0)).length() < 1e-5);
+        assert!((centers[3] - series_point(&data, 0.25)).length() < 1e-5);
+    }
+}
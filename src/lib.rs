@@ -0,0 +1,101 @@
+pub mod analysis;
+pub mod coefficients;
+pub mod error;
+pub mod fft_drawer;
+pub mod path_util;
+pub mod svg_reader;
+pub mod viewport;
+pub mod visualizer;
+
+pub use analysis::{choose_wave_count, reconstruction_error, ReconstructionError, WaveTarget};
+pub use coefficients::{read_coefficients_file, CoefficientsFile};
+pub use error::Error;
+pub use fft_drawer::{epicycle_centers, sample_series, series_point, DrawData, Drawing, Selection};
+pub use svg_reader::{read_svg_file, read_svg_str, SvgDocument};
+pub use viewport::Viewport;
+pub use visualizer::Visualizer;
+
+pub use lyon_path::Path;
+
+use path_util::path_to_fft;
+
+pub const DEFAULT_SAMPLES: usize = 10240;
+pub const DEFAULT_WAVES: usize = 201;
+
+// Compute the waves drawing a path, using at most as many waves as samples
+pub fn path_to_draw_data(path: Path, num_sample: usize, num_wave: usize) -> Result<Vec<DrawData>, Error> {
+    path_to_draw_data_by(path, num_sample, num_wave, Selection::LowestFrequency)
+}
+
+pub fn path_to_draw_data_by(path: Path, num_sample: usize, num_wave: usize, selection: Selection) -> Result<Vec<DrawData>, Error> {
+    let fft_result = path_to_fft(path, num_sample)?;
+    Ok(fft_drawer::select_draw_data_by(&fft_result, num_wave.min(num_sample), selection))
+}
+
+// Each path gets its own set of waves
+pub fn paths_to_drawings(paths: Vec<Path>, num_sample: usize, num_wave: usize) -> Result<Vec<Drawing>, Error> {
+    paths_to_drawings_by(paths, num_sample, num_wave, Selection::LowestFrequency)
+}
+
+pub fn paths_to_drawings_by(paths: Vec<Path>, num_sample: usize, num_wave: usize, selection: Selection) -> Result<Vec<Drawing>, Error> {
+    if paths.is_empty() {
+        return Err(Error::MissingPath);
+    }
+    paths.into_iter()
+        .map(|path| path_to_drawing(path, num_sample, num_wave, selection))
+        .collect()
+}
+
+// Each path gets the fewest waves meeting the target
+pub fn paths_to_drawings_for_target(paths: Vec<Path>, num_sample: usize, target: WaveTarget, selection: Selection) -> Result<Vec<Drawing>, Error> {
+    if paths.is_empty() {
+        return Err(Error::MissingPath);
+    }
+    paths.into_iter()
+        .map(|path| {
+            let num_wave = choose_wave_count(&path, num_sample, target, selection)?;
+            path_to_drawing(path, num_sample, num_wave, selection)
+        })
+        .collect()
+}
+
+fn path_to_drawing(path: Path, num_sample: usize, num_wave: usize, selection: Selection) -> Result<Drawing, Error> {
+    let waves = path_to_draw_data_by(path.clone(), num_sample, num_wave, selection)?;
+    let mut drawing = Drawing::new(waves, Some(path));
+    drawing.sample_count = num_sample;
+    Ok(drawing)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn draws_every_path_of_a_document() {
+        let document = read_svg_str("<svg><rect width=\"10\" height=\"10\"/><circle r=\"5\"/></svg>").unwrap();
+        let drawings = paths_to_drawings(document.paths, 64, 5).unwrap();
+        assert_eq!(drawings.len(), 2);
+        assert!(drawings.iter().all(|d| d.waves.len() == 5 && d.original.is_some()));
+    }
+
+    #[test]
+    fn uses_no_more_waves_than_samples() {
+        let path = path_util::build_path_from_svg("M 0 0 L 10 0 L 10 10 Z").unwrap();
+        let data = path_to_draw_data(path, 8, 201).unwrap();
+        assert_eq!(data.len(), 8);
+    }
+
+    #[test]
+    fn selects_the_waves_by_magnitude() {
+        // A square only has odd harmonics, the largest being 1, -3, 5...
+        let path = path_util::build_path_from_svg("M 0 0 L 10 0 L 10 10 L 0 10 Z").unwrap();
+        let data = path_to_draw_data_by(path, 256, 3, Selection::LargestMagnitude).unwrap();
+        let frequencies: Vec<f32> = data.iter().map(|d| d.frequency).collect();
+        assert_eq!(frequencies, vec![0.0, 1.0, -3.0]);
+    }
+
+    #[test]
+    fn rejects_an_empty_drawing() {
+        assert!(matches!(paths_to_drawings(Vec::new(), 64, 5), Err(Error::MissingPath)));
+    }
+}
@@ -1,23 +1,36 @@
-mod fft_drawer;
-mod visualizer;
-mod path_util;
+use fourier_svg::{
+    reconstruction_error,
+    read_svg_file,
+    read_coefficients_file,
+    paths_to_drawings_by,
+    paths_to_drawings_for_target,
+    Error,
+    Selection,
+    WaveTarget,
+    DEFAULT_SAMPLES,
+    DEFAULT_WAVES
+};
 
 // Visualizer
-use visualizer::Visualizer;
-use visualizer::html_visualizer::HTMLVisualizer;
+use fourier_svg::visualizer::{RenderOptions, Output, Color};
+use fourier_svg::visualizer::theme::read_theme;
+use fourier_svg::visualizer::registry::{find_visualizer, visualizer_descriptions, visualizer_names};
+use fourier_svg::visualizer::csv_visualizer::write_samples_csv;
 
 // Path util
-use path_util::{
-    build_path_from_svg,
-    path_to_fft
-};
+use fourier_svg::path_util::build_path_from_svg;
+
+// Viewport
+use fourier_svg::viewport::{Viewport, paths_bounds};
 
-use svg::node::element::tag::Path;
-use svg::parser::Event;
+use std::process;
 
-use clap::{Arg, App, AppSettings};
+use clap::{Arg, App, AppSettings, ArgMatches};
 
 fn main() {
+    let visualizers = visualizer_names();
+    let visualizer_help = format!("Choose how to render the drawing:\n{}", visualizer_descriptions());
+
     // Add param
     let app = App::new("Fourier SVG Drawer")
         .version("1.0.0")
@@ -32,7 +45,12 @@ fn main() {
         .arg(Arg::with_name("SVG file")
             .short("f")
             .long("file")
-            .help("Draw the first SVG path in file")
+            .help("Draw all the SVG paths and shapes in file")
+            .takes_value(true))
+        .arg(Arg::with_name("Coefficient file")
+            .short("c")
+            .long("coefficients")
+            .help("Draw the waves saved in a JSON coefficient file, without computing them again")
             .takes_value(true))
         .arg(Arg::with_name("Number of sample points")
             .short("s")
@@ -43,63 +61,342 @@ fn main() {
             .short("w")
             .long("wave")
             .help("Use how many waves to draw the path")
+            .takes_value(true))
+        .arg(Arg::with_name("Output width")
+            .long("width")
+            .help("Width of the output canvas")
+            .takes_value(true))
+        .arg(Arg::with_name("Output height")
+            .long("height")
+            .help("Height of the output canvas")
+            .takes_value(true))
+        .arg(Arg::with_name("Padding")
+            .long("padding")
+            .help("Space kept between the drawing and the canvas border")
+            .takes_value(true))
+        .arg(Arg::with_name("Fit")
+            .long("fit")
+            .help("Fit the SVG viewBox, or only the content of the drawing, to the canvas")
+            .possible_values(&["viewbox", "content"])
+            .takes_value(true))
+        .arg(Arg::with_name("Visualizer")
+            .short("v")
+            .long("visualizer")
+            .help("Choose how to render the drawing")
+            .long_help(&visualizer_help)
+            .possible_values(&visualizers)
+            .hide_possible_values(true)
+            .takes_value(true))
+        .arg(Arg::with_name("Output")
+            .short("o")
+            .long("output")
+            .help("Write the result to this file, or to the standard output with -")
+            .takes_value(true))
+        .arg(Arg::with_name("Wave counts")
+            .long("layers")
+            .help("Also draw the curve with only these numbers of waves, separated by commas")
+            .takes_value(true))
+        .arg(Arg::with_name("Show original")
+            .long("original")
+            .help("Draw the original path under the result"))
+        .arg(Arg::with_name("Number of frames")
+            .long("frames")
+            .help("Number of images drawn along a revolution")
+            .takes_value(true))
+        .arg(Arg::with_name("No antialiasing")
+            .long("no-antialias")
+            .help("Do not smooth the edges of the images"))
+        .arg(Arg::with_name("Period")
+            .long("period")
+            .help("Seconds taken by a revolution of the animations")
+            .takes_value(true))
+        .arg(Arg::with_name("Trail length")
+            .long("trail-length")
+            .help("Portion of a revolution covered by the trail, a whole one by default")
+            .takes_value(true))
+        .arg(Arg::with_name("Frames per second")
+            .long("fps")
+            .help("Frames per second of the videos")
+            .takes_value(true))
+        .arg(Arg::with_name("Duration")
+            .long("duration")
+            .help("Length of the videos in seconds, replacing the number of revolutions")
+            .takes_value(true))
+        .arg(Arg::with_name("Number of revolutions")
+            .long("revolutions")
+            .help("Length of the videos in revolutions, one by default")
+            .takes_value(true))
+        .arg(Arg::with_name("Number of colors")
+            .long("colors")
+            .help("Number of colors of the GIF palette, from 64 to 256")
+            .takes_value(true))
+        .arg(Arg::with_name("Sample file")
+            .long("samples-csv")
+            .help("Also write the points sampled along the paths to this CSV file")
+            .takes_value(true))
+        .arg(Arg::with_name("Report")
+            .long("report")
+            .help("Print how far the drawing is from the original paths"))
+        .arg(Arg::with_name("Target energy")
+            .long("target-energy")
+            .help("Use the fewest waves keeping this share of the energy, like 0.999 or 99.9%")
+            .takes_value(true)
+            .conflicts_with_all(&["Number of waves", "Maximum error"]))
+        .arg(Arg::with_name("Maximum error")
+            .long("max-error")
+            .help("Use the fewest waves drawing no farther than this from the sampled points")
+            .takes_value(true)
+            .conflicts_with("Number of waves"))
+        .arg(Arg::with_name("Selection")
+            .long("select")
+            .help("Keep the waves of the lowest frequencies, or the largest ones")
+            .takes_value(true)
+            .possible_values(&["frequency", "magnitude"]))
+        .arg(Arg::with_name("Sort arms")
+            .long("sort-arms")
+            .help("Chain the arms from the longest to the shortest"))
+        .arg(Arg::with_name("Show circles")
+            .long("circles")
+            .help("Draw the circle each arm turns along"))
+        .arg(Arg::with_name("Minimum radius")
+            .long("min-radius")
+            .help("Do not draw the arms shorter than this, in pixels")
+            .takes_value(true))
+        .arg(Arg::with_name("Show tip")
+            .long("tip")
+            .help("Mark the end of the arms, where the curve is drawn"))
+        .arg(Arg::with_name("Theme")
+            .long("theme")
+            .help("Look of the drawing: light, dark, or a JSON theme file")
+            .takes_value(true))
+        .arg(Arg::with_name("Background")
+            .long("background")
+            .help("Background color, like white, #202020 or rgba(0, 0, 0, 0.5)")
+            .takes_value(true))
+        .arg(Arg::with_name("Arm color")
+            .long("arm-color")
+            .help("Color of the arms")
+            .takes_value(true))
+        .arg(Arg::with_name("Circle color")
+            .long("circle-color")
+            .help("Color of the circles of the arms")
+            .takes_value(true))
+        .arg(Arg::with_name("Trail color")
+            .long("trail-color")
+            .help("Color of the drawn curve")
+            .takes_value(true))
+        .arg(Arg::with_name("Trail end color")
+            .long("trail-end-color")
+            .help("Color the trail turns into at its end, instead of fading out")
+            .takes_value(true))
+        .arg(Arg::with_name("Original color")
+            .long("original-color")
+            .help("Color of the original path")
+            .takes_value(true))
+        .arg(Arg::with_name("Arm width")
+            .long("arm-width")
+            .help("Width of the arms and their circles")
+            .takes_value(true))
+        .arg(Arg::with_name("Trail width")
+            .long("trail-width")
+            .help("Width of the drawn curve and the original path")
             .takes_value(true));
     let matches = app.get_matches();
 
+    if let Err(err) = run(&matches) {
+        eprintln!("Error: {}", err);
+        process::exit(1);
+    }
+}
+
+fn run(matches: &ArgMatches) -> Result<(), Error> {
     // SVG source args
     let arg_path = matches.value_of("SVG Path").unwrap_or("");
     let arg_svg_file = matches.value_of("SVG file").unwrap_or("");
+    let arg_coefficients = matches.value_of("Coefficient file").unwrap_or("");
 
     // FFT config args
-    let arg_sample = matches.value_of("Number of sample points").unwrap_or("10240");
-    let arg_wave = matches.value_of("Number of waves").unwrap_or("201");
-
-    // Retrieve svg from web or local file
-    let mut svg_string: String = "".to_string();
-    if arg_svg_file.len() > 0 {
-        // Read path from svg file
-        let mut content = String::new();
-        for event in svg::open(arg_svg_file, &mut content).unwrap() {
-            match event {
-                Event::Tag(Path, _, attributes) => {
-                    svg_string = attributes.get("d").unwrap().to_string();
-                    // svg_string = data;
-                    break;
+    let arg_sample = matches.value_of("Number of sample points");
+    let arg_wave = matches.value_of("Number of waves");
+
+    // Output args
+    let arg_fit = matches.value_of("Fit").unwrap_or("viewbox");
+    let arg_visualizer = matches.value_of("Visualizer").unwrap_or(visualizer_names()[0]);
+    let arg_output = matches.value_of("Output");
+    let arg_layers = matches.value_of("Wave counts").unwrap_or("");
+
+    let num_sample = arg_sample.and_then(|s| s.parse::<usize>().ok()).unwrap_or(DEFAULT_SAMPLES);
+    let num_wave = arg_wave.and_then(|w| w.parse::<usize>().ok()).unwrap_or(DEFAULT_WAVES);
+    let selection = match matches.value_of("Selection") {
+        Some("magnitude") => Selection::LargestMagnitude,
+        _ => Selection::LowestFrequency,
+    };
+
+    let (drawings, bounds) = if !arg_coefficients.is_empty() {
+        // Draw saved waves, fitted like when they were saved
+        let file = read_coefficients_file(arg_coefficients)?;
+        (file.to_drawings()?, file.bounds())
+    } else {
+        // Retrieve svg from web or local file
+        let (paths, frame) = if !arg_svg_file.is_empty() {
+            // Read paths and shapes from svg file
+            let document = read_svg_file(arg_svg_file)?;
+            let frame = document.frame();
+            (document.paths, frame)
+        } else if !arg_path.is_empty() {
+            // Read path from svg path string
+            (vec![build_path_from_svg(arg_path)?], None)
+        } else {
+            return Err(Error::MissingPath);
+        };
+
+        // Fit the drawing to the canvas
+        let bounds = match frame {
+            Some(frame) if arg_fit == "viewbox" => frame,
+            _ => paths_bounds(&paths),
+        };
+        let drawings = match wave_target(matches)? {
+            Some(target) => {
+                let drawings = paths_to_drawings_for_target(paths, num_sample, target, selection)?;
+                for (i, drawing) in drawings.iter().enumerate() {
+                    report(&format!("path {}: {} waves chosen", i, drawing.waves.len()), arg_output);
                 }
-                _ => {}
+                drawings
             }
+            None => paths_to_drawings_by(paths, num_sample, num_wave, selection)?,
+        };
+        (drawings, bounds)
+    };
+
+    if let Some(arg_samples) = matches.value_of("Sample file") {
+        write_samples_csv(&drawings, &Output::from_arg(arg_samples))?;
+    }
+    if matches.is_present("Report") {
+        for (i, drawing) in drawings.iter().enumerate() {
+            let original = match &drawing.original {
+                Some(original) => original,
+                None => continue,
+            };
+            let error = reconstruction_error(&drawing.waves, original, drawing.sample_count)?;
+            report(&format!("path {}: {} waves, max {:.4}, mean {:.4}, rms {:.4}, hausdorff {:.4}",
+                i, drawing.waves.len(), error.max, error.mean, error.rms, error.hausdorff), arg_output);
         }
-    } else if arg_path.len() > 0 {
-        // Read path from svg path string
-        svg_string = arg_path.to_string();
-    } else {
-        println!("No SVG path provided.");
-        return;
     }
 
-    let num_sample = arg_sample.parse::<usize>().unwrap_or(10240);
-    let mut num_wave = arg_wave.parse::<usize>().unwrap_or(201);
+    let viewport = Viewport::new(
+        bounds,
+        canvas_length(matches, "Output width", "width", 800.0)?,
+        canvas_length(matches, "Output height", "height", 600.0)?,
+        canvas_length(matches, "Padding", "padding", 20.0)?);
+    viewport.validate()?;
 
-    // Make sure num_sample >= num_wave
-    if num_sample < num_wave {
-        num_wave = num_sample;
+    let mut options = RenderOptions::new(viewport);
+    if let Some(arg_theme) = matches.value_of("Theme") {
+        options.theme = read_theme(arg_theme)?;
+    }
+    let theme = &mut options.theme;
+    let color = |name: &str| matches.value_of(name).map(|c| c.parse::<Color>()).transpose();
+    if let Some(background) = color("Background")? {
+        theme.background = background;
+    }
+    if let Some(arm_color) = color("Arm color")? {
+        theme.arm_color = arm_color;
+    }
+    if let Some(circle_color) = color("Circle color")? {
+        theme.circle_color = circle_color;
+    }
+    if let Some(trail_color) = color("Trail color")? {
+        theme.trail_color = trail_color;
+    }
+    if let Some(trail_end_color) = color("Trail end color")? {
+        theme.trail_end_color = Some(trail_end_color);
+    }
+    if let Some(original_color) = color("Original color")? {
+        theme.original_color = original_color;
+    }
+    if let Some(width) = matches.value_of("Arm width").and_then(|w| w.parse::<f32>().ok()) {
+        theme.arm_width = width;
+    }
+    if let Some(width) = matches.value_of("Trail width").and_then(|w| w.parse::<f32>().ok()) {
+        theme.trail_width = width;
+    }
+    options.wave_counts = arg_layers.split(',')
+        .filter_map(|count| count.trim().parse::<usize>().ok())
+        .collect();
+    options.show_original = matches.is_present("Show original");
+    if let Some(arg_frames) = matches.value_of("Number of frames") {
+        options.frames = arg_frames.parse::<usize>().map_err(|_| Error::InvalidTiming(format!("frame count {}", arg_frames)))?;
+    }
+    options.antialias = !matches.is_present("No antialiasing");
+    options.sort_by_radius = matches.is_present("Sort arms");
+    options.show_circles = matches.is_present("Show circles");
+    options.show_tip = matches.is_present("Show tip");
+    if let Some(radius) = matches.value_of("Minimum radius").and_then(|r| r.parse::<f32>().ok()) {
+        options.min_arm_radius = radius;
+    }
+    if let Some(arg_period) = matches.value_of("Period") {
+        options.period = arg_period.parse::<f32>().map_err(|_| Error::InvalidTiming(format!("period {}", arg_period)))?;
+    }
+    if let Some(trail_length) = matches.value_of("Trail length").and_then(|l| l.parse::<f32>().ok()) {
+        options.trail_length = trail_length;
+    }
+    if let Some(arg_fps) = matches.value_of("Frames per second") {
+        options.fps = arg_fps.parse::<f32>().map_err(|_| Error::InvalidTiming(format!("frame rate {}", arg_fps)))?;
+    }
+    if let Some(arg_revolutions) = matches.value_of("Number of revolutions") {
+        options.revolutions = arg_revolutions.parse::<f32>().map_err(|_| Error::InvalidTiming(format!("revolution count {}", arg_revolutions)))?;
+    }
+    if let Some(arg_duration) = matches.value_of("Duration") {
+        options.duration = Some(arg_duration.parse::<f32>().map_err(|_| Error::InvalidTiming(format!("duration {}", arg_duration)))?);
+    }
+    if let Some(arg_colors) = matches.value_of("Number of colors") {
+        options.palette_size = arg_colors.parse::<usize>().map_err(|_| Error::InvalidPaletteSize(arg_colors.to_string()))?;
     }
 
-    let path = build_path_from_svg(&svg_string);
+    // Render with the chosen visualizer, into output.<extension> by default
+    let entry = find_visualizer(arg_visualizer)
+        .ok_or_else(|| Error::UnknownVisualizer(arg_visualizer.to_string()))?;
+    let output = match arg_output {
+        Some(arg) => Output::from_arg(arg),
+        None => Output::from_arg(&format!("output.{}", entry.extension)),
+    };
+    let visualizer = (entry.create)(output);
+    visualizer.render(&drawings, &options)
 
-    let fft_size = num_sample;
-    let fft_result = path_to_fft(path, fft_size);
+}
+
+// The ranges are checked by the viewport
+fn canvas_length(matches: &ArgMatches, name: &str, length: &str, default: f32) -> Result<f32, Error> {
+    match matches.value_of(name) {
+        Some(arg) => arg.parse::<f32>().map_err(|_| Error::InvalidCanvas(format!("{} {}, expected a number", length, arg))),
+        None => Ok(default),
+    }
+}
 
-    // Temporally output to json
-    let mut data = Vec::new();
-    data.push(fft_drawer::DrawData::new_from_complex(0 as f32, fft_result[0]));
-    // Can change from param
-    for i in 1..((num_wave + 1) / 2) {
-        data.push(fft_drawer::DrawData::new_from_complex(i as f32, fft_result[i]));
-        data.push(fft_drawer::DrawData::new_from_complex((0 - i as i32) as f32, fft_result[fft_size - i]));
+// The ranges are checked along with the paths
+fn wave_target(matches: &ArgMatches) -> Result<Option<WaveTarget>, Error> {
+    if let Some(arg_energy) = matches.value_of("Target energy") {
+        let invalid = || Error::InvalidTarget(format!("energy {}, expected a share in (0, 1] or a percentage", arg_energy));
+        // Either a share or a percentage
+        let share = match arg_energy.strip_suffix('%') {
+            Some(percent) => percent.parse::<f32>().map_err(|_| invalid())? / 100.0,
+            None => arg_energy.parse::<f32>().map_err(|_| invalid())?,
+        };
+        return Ok(Some(WaveTarget::Energy(share)));
     }
+    if let Some(arg_error) = matches.value_of("Maximum error") {
+        let max_error = arg_error.parse::<f32>()
+            .map_err(|_| Error::InvalidTarget(format!("maximum error {}, expected a positive distance", arg_error)))?;
+        return Ok(Some(WaveTarget::MaxError(max_error)));
+    }
+    Ok(None)
+}
 
-    // TODO: Add an option to choose a different visualizer
-    let html_visualizer = HTMLVisualizer::new("output.html".to_string());
-    html_visualizer.render(data);
+fn report(line: &str, arg_output: Option<&str>) {
+    // Keep the standard output for the result
+    if arg_output == Some("-") {
+        eprintln!("{}", line);
+    } else {
+        println!("{}", line);
+    }
 }
@@ -1,19 +1,122 @@
+use lyon_path::builder::SvgPathBuilder;
 use lyon_path::iterator::*;
-use lyon_path::{Path, PathEvent};
+use lyon_path::math::{point, vector, Angle, Point};
+use lyon_path::{ArcFlags, Path, PathEvent};
+use lyon_svg::parser::{self as svgtypes, PathParser};
 use lyon_svg::path_utils::build_path;
 
 use rustfft::{FftPlanner, num_complex::Complex};
 
-pub fn build_path_from_svg(svg_commands: &str) -> Path {
-    let svg_builder = Path::builder().with_svg();
-    match build_path(svg_builder, svg_commands) {
-        Ok (path) => {
-            return path;
+use crate::error::Error;
+
+pub fn build_path_from_svg(svg_commands: &str) -> Result<Path, Error> {
+    // The builder skips what it cannot parse, so check the commands first
+    for segment in PathParser::from(svg_commands) {
+        if let Err(err) = segment {
+            // The parser counts the characters from 1
+            let position = match err {
+                svgtypes::Error::UnexpectedData(pos)
+                | svgtypes::Error::InvalidChar(_, pos)
+                | svgtypes::Error::InvalidString(_, pos)
+                | svgtypes::Error::InvalidNumber(pos) => Some(pos),
+                // Just after the last character
+                svgtypes::Error::UnexpectedEndOfStream => Some(svg_commands.chars().count() + 1),
+                _ => None,
+            };
+            return Err(Error::InvalidPathData { position });
         }
-        _ => {
-            panic!();
+    }
+
+    let svg_builder = Path::builder().with_svg();
+    build_path(svg_builder, svg_commands).map_err(|_| Error::InvalidPathData { position: None })
+}
+
+// Basic shapes are built following the equivalent paths given by the SVG spec,
+// so that they start at the same point and go in the same direction.
+pub fn build_path_from_rect(x: f32, y: f32, width: f32, height: f32, rx: f32, ry: f32) -> Option<Path> {
+    if width <= 0.0 || height <= 0.0 {
+        return None;
+    }
+    let rx = rx.min(width / 2.0);
+    let ry = ry.min(height / 2.0);
+
+    let mut builder = Path::builder().with_svg();
+    if rx > 0.0 && ry > 0.0 {
+        let radii = vector(rx, ry);
+        let flags = ArcFlags { large_arc: false, sweep: true };
+        builder.move_to(point(x + rx, y));
+        builder.line_to(point(x + width - rx, y));
+        builder.arc_to(radii, Angle::zero(), flags, point(x + width, y + ry));
+        builder.line_to(point(x + width, y + height - ry));
+        builder.arc_to(radii, Angle::zero(), flags, point(x + width - rx, y + height));
+        builder.line_to(point(x + rx, y + height));
+        builder.arc_to(radii, Angle::zero(), flags, point(x, y + height - ry));
+        builder.line_to(point(x, y + ry));
+        builder.arc_to(radii, Angle::zero(), flags, point(x + rx, y));
+    } else {
+        builder.move_to(point(x, y));
+        builder.line_to(point(x + width, y));
+        builder.line_to(point(x + width, y + height));
+        builder.line_to(point(x, y + height));
+    }
+    builder.close();
+    Some(builder.build())
+}
+
+pub fn build_path_from_ellipse(cx: f32, cy: f32, rx: f32, ry: f32) -> Option<Path> {
+    if rx <= 0.0 || ry <= 0.0 {
+        return None;
+    }
+
+    let mut builder = Path::builder().with_svg();
+    let radii = vector(rx, ry);
+    let flags = ArcFlags { large_arc: false, sweep: true };
+    builder.move_to(point(cx + rx, cy));
+    builder.arc_to(radii, Angle::zero(), flags, point(cx, cy + ry));
+    builder.arc_to(radii, Angle::zero(), flags, point(cx - rx, cy));
+    builder.arc_to(radii, Angle::zero(), flags, point(cx, cy - ry));
+    builder.arc_to(radii, Angle::zero(), flags, point(cx + rx, cy));
+    builder.close();
+    Some(builder.build())
+}
+
+pub fn build_path_from_points(points: &[Point], close: bool) -> Option<Path> {
+    if points.len() < 2 {
+        return None;
+    }
+
+    let mut builder = Path::builder().with_svg();
+    builder.move_to(points[0]);
+    for p in &points[1..] {
+        builder.line_to(*p);
+    }
+    if close {
+        builder.close();
+    }
+    Some(builder.build())
+}
+
+// SVG path data of a path, for drawing it back in the outputs
+pub fn path_to_svg(path: &Path) -> String {
+    let mut commands = Vec::new();
+    for evt in path.iter() {
+        match evt {
+            PathEvent::Begin { at } => commands.push(format!("M{} {}", at.x, at.y)),
+            PathEvent::Line { to, .. } => commands.push(format!("L{} {}", to.x, to.y)),
+            PathEvent::Quadratic { ctrl, to, .. } => {
+                commands.push(format!("Q{} {} {} {}", ctrl.x, ctrl.y, to.x, to.y))
+            }
+            PathEvent::Cubic { ctrl1, ctrl2, to, .. } => {
+                commands.push(format!("C{} {} {} {} {} {}", ctrl1.x, ctrl1.y, ctrl2.x, ctrl2.y, to.x, to.y))
+            }
+            PathEvent::End { close, .. } => {
+                if close {
+                    commands.push("Z".to_string());
+                }
+            }
         }
     }
+    commands.join(" ")
 }
 
 pub fn compute_path_length(path: &Path) -> f32 {
@@ -30,20 +133,29 @@ pub fn compute_path_length(path: &Path) -> f32 {
     for evt in flattened_iter {
         match evt {
             PathEvent::Begin { at: _ } => {}
-            PathEvent::Line { from, to } => { total_length += (to - from).length(); }
+            // Flattening only gives lines, curves would be approximated by their chord anyway
+            PathEvent::Line { from, to }
+            | PathEvent::Quadratic { from, to, .. }
+            | PathEvent::Cubic { from, to, .. } => { total_length += (to - from).length(); }
             PathEvent::End { last, first, close } => {
                 if close {
                     // Add the closed path
                     total_length += (first - last).length();
                 }
             }
-            _ => { panic!() }
         }
     }
     total_length
 }
 
-pub fn construct_sample_points(path: &Path, total_length: f32, n_sample: usize) -> Vec<Complex<f32>> {
+pub fn construct_sample_points(path: &Path, total_length: f32, n_sample: usize) -> Result<Vec<Complex<f32>>, Error> {
+    if n_sample == 0 {
+        return Err(Error::InvalidSampleCount(n_sample));
+    }
+    if total_length <= 0.0 || !total_length.is_finite() {
+        return Err(Error::EmptyPath);
+    }
+
     let mut samples = Vec::new();
 
     // Make it an iterator over simpler primitives flattened events,
@@ -66,22 +178,23 @@ pub fn construct_sample_points(path: &Path, total_length: f32, n_sample: usize)
                 // println!("Add sample point {:?} at {:?} for begin", itered_index, at);
                 itered_index += 1;
             }
-            PathEvent::Line { from, to } => {
+            PathEvent::Line { from, to }
+            | PathEvent::Quadratic { from, to, .. }
+            | PathEvent::Cubic { from, to, .. } => {
                 let next_sample_length = sample_length * (itered_index as f32);
                 let current_line_length = (to - from).length();
                 let mut last_added_sample_on_this_segment: f32 = 0.0;
-                if itered_length < next_sample_length {
-                    if itered_length + current_line_length >= next_sample_length {
-                        last_added_sample_on_this_segment = sample_length
-                            - (itered_length - sample_length * ((itered_index - 1) as f32));
-                        // Add a sample point on the segment
-                        let sample = from + (to - from) * 
-                            ((last_added_sample_on_this_segment) / current_line_length);
-                        samples.push(Complex{ re: sample.x, im: sample.y });
-                        // println!("Add sample point {:?} at {:?}", itered_index, sample);
-                        // Ready to find the next sample point
-                        itered_index += 1;
-                    }
+                if itered_length < next_sample_length
+                    && itered_length + current_line_length >= next_sample_length {
+                    last_added_sample_on_this_segment = sample_length
+                        - (itered_length - sample_length * ((itered_index - 1) as f32));
+                    // Add a sample point on the segment
+                    let sample = from + (to - from) * 
+                        ((last_added_sample_on_this_segment) / current_line_length);
+                    samples.push(Complex{ re: sample.x, im: sample.y });
+                    // println!("Add sample point {:?} at {:?}", itered_index, sample);
+                    // Ready to find the next sample point
+                    itered_index += 1;
                 }
                 // println!("last_added_sample_on_this_segment {:?}", last_added_sample_on_this_segment);
 
@@ -110,18 +223,17 @@ pub fn construct_sample_points(path: &Path, total_length: f32, n_sample: usize)
                     let next_sample_length = sample_length * (itered_index as f32);
                     let current_line_length = (to - from).length();
                     let mut last_added_sample_on_this_segment: f32 = 0.0;
-                    if itered_length < next_sample_length {
-                        if itered_length + current_line_length >= next_sample_length {
-                            last_added_sample_on_this_segment = sample_length
-                                - (itered_length - sample_length * ((itered_index - 1) as f32));
-                            // Add a sample point on the segment
-                            let sample = from + (to - from) * 
-                                ((last_added_sample_on_this_segment) / current_line_length);
-                            samples.push(Complex{ re: sample.x, im: sample.y });
-                            // println!("Add sample point {:?} at {:?}", itered_index, sample);
-                            // Ready to find the next sample point
-                            itered_index += 1;
-                        }
+                    if itered_length < next_sample_length
+                        && itered_length + current_line_length >= next_sample_length {
+                        last_added_sample_on_this_segment = sample_length
+                            - (itered_length - sample_length * ((itered_index - 1) as f32));
+                        // Add a sample point on the segment
+                        let sample = from + (to - from) * 
+                            ((last_added_sample_on_this_segment) / current_line_length);
+                        samples.push(Complex{ re: sample.x, im: sample.y });
+                        // println!("Add sample point {:?} at {:?}", itered_index, sample);
+                        // Ready to find the next sample point
+                        itered_index += 1;
                     }
                     // println!("last_added_sample_on_this_segment {:?}", last_added_sample_on_this_segment);
 
@@ -139,19 +251,26 @@ pub fn construct_sample_points(path: &Path, total_length: f32, n_sample: usize)
                     }
                 }
             }
-            _ => { panic!() }
         }
     }
-    samples
+    Ok(samples)
 }
 
-pub fn path_to_fft(path: Path, n_sample: usize) -> Vec<Complex<f32>> {
-    let path_length = compute_path_length(&path);
-    let mut samples = construct_sample_points(&path, path_length, n_sample);
+// Exactly n_sample points evenly spread along the path, as given to the FFT
+pub fn sample_path(path: &Path, n_sample: usize) -> Result<Vec<Complex<f32>>, Error> {
+    let path_length = compute_path_length(path);
+    let mut samples = construct_sample_points(path, path_length, n_sample)?;
 
-    while samples.len() > n_sample {
-        samples.remove(n_sample);
+    // Rounding may give one sample too many or too few
+    samples.truncate(n_sample);
+    while samples.len() < n_sample {
+        samples.push(samples[samples.len() - 1]);
     }
+    Ok(samples)
+}
+
+pub fn path_to_fft(path: Path, n_sample: usize) -> Result<Vec<Complex<f32>>, Error> {
+    let mut samples = sample_path(&path, n_sample)?;
     let mut planner = FftPlanner::<f32>::new();
     let fft = planner.plan_fft_forward(n_sample);
 
@@ -160,5 +279,74 @@ pub fn path_to_fft(path: Path, n_sample: usize) -> Vec<Complex<f32>> {
     for i in 0..samples.len() {
         samples[i] = samples[i] / samples.len() as f32;
     }
-    samples
+    Ok(samples)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_the_position_of_invalid_data() {
+        match build_path_from_svg("M 0 0 L 10 x") {
+            Err(Error::InvalidPathData { position }) => assert_eq!(position, Some(12)),
+            _ => panic!("the path data should be invalid"),
+        }
+        // Data ending too early is faulty just after its end
+        for data in ["M 0 0 L", "M 0 0 L 10"] {
+            match build_path_from_svg(data) {
+                Err(Error::InvalidPathData { position }) => assert_eq!(position, Some(data.len() + 1)),
+                _ => panic!("the path data should be invalid"),
+            }
+        }
+    }
+
+    #[test]
+    fn writes_paths_back_to_svg() {
+        let path = build_path_from_svg("M 0 0 L 10 0 Q 10 10 0 10 Z M 20 20 L 30 30").unwrap();
+        assert_eq!(path_to_svg(&path), "M0 0 L10 0 Q10 10 0 10 Z M20 20 L30 30");
+    }
+
+    #[test]
+    fn measures_closed_paths() {
+        let path = build_path_from_svg("M 0 0 L 10 0 L 10 10 L 0 10 Z").unwrap();
+        assert!((compute_path_length(&path) - 40.0).abs() < 1e-3);
+
+        let rect = build_path_from_rect(0.0, 0.0, 10.0, 10.0, 0.0, 0.0).unwrap();
+        assert!((compute_path_length(&rect) - 40.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn skips_degenerate_shapes() {
+        assert!(build_path_from_rect(0.0, 0.0, 0.0, 10.0, 0.0, 0.0).is_none());
+        assert!(build_path_from_ellipse(0.0, 0.0, 5.0, 0.0).is_none());
+        assert!(build_path_from_points(&[point(0.0, 0.0)], false).is_none());
+    }
+
+    #[test]
+    fn samples_evenly_along_the_path() {
+        let path = build_path_from_svg("M 0 0 L 10 0 L 10 10 L 0 10 Z").unwrap();
+        let samples = construct_sample_points(&path, 40.0, 4).unwrap();
+        assert_eq!(samples[0], Complex { re: 0.0, im: 0.0 });
+        assert!((samples[1] - Complex { re: 10.0, im: 0.0 }).norm() < 1e-3);
+        assert!((samples[2] - Complex { re: 10.0, im: 10.0 }).norm() < 1e-3);
+    }
+
+    #[test]
+    fn rejects_paths_that_cannot_be_sampled() {
+        let path = build_path_from_svg("M 0 0 L 10 0").unwrap();
+        assert!(matches!(path_to_fft(path.clone(), 0), Err(Error::InvalidSampleCount(0))));
+
+        let point = build_path_from_svg("M 5 5").unwrap();
+        assert!(matches!(path_to_fft(point, 16), Err(Error::EmptyPath)));
+    }
+
+    #[test]
+    fn circle_has_a_single_frequency() {
+        let circle = build_path_from_ellipse(0.0, 0.0, 10.0, 10.0).unwrap();
+        let fft_result = path_to_fft(circle, 256).unwrap();
+        assert!((fft_result[1].norm() - 10.0).abs() < 0.1);
+        assert!(fft_result[0].norm() < 0.1);
+        assert!(fft_result[255].norm() < 0.1);
+    }
 }
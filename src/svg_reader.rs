@@ -0,0 +1,278 @@
+use lyon_path::math::{point, rect, Rect, Transform};
+use lyon_path::Path;
+use lyon_svg::parser::{self as svgtypes, Length, LengthUnit, PointsParser, ViewBox};
+
+use svg::node::Attributes;
+use svg::node::element::tag::{self, Type};
+use svg::parser::Event;
+
+use crate::error::Error;
+use crate::path_util::{
+    build_path_from_svg,
+    build_path_from_rect,
+    build_path_from_ellipse,
+    build_path_from_points,
+    compute_path_length
+};
+
+pub struct SvgDocument {
+    pub paths: Vec<Path>,
+    pub view_box: Option<Rect>,
+    pub width: Option<f32>,
+    pub height: Option<f32>,
+}
+
+impl SvgDocument {
+    // The area the author meant to show: the viewBox, or the viewport size
+    pub fn frame(&self) -> Option<Rect> {
+        match (self.view_box, self.width, self.height) {
+            (Some(view_box), _, _) => Some(view_box),
+            (None, Some(width), Some(height)) => Some(rect(0.0, 0.0, width, height)),
+            _ => None,
+        }
+    }
+}
+
+pub fn read_svg_file(file_name: &str) -> Result<SvgDocument, Error> {
+    let content = std::fs::read_to_string(file_name).map_err(|err| Error::io(file_name, err))?;
+    read_svg_str(&content)
+}
+
+pub fn read_svg_str(content: &str) -> Result<SvgDocument, Error> {
+    let mut document = SvgDocument {
+        paths: Vec::new(),
+        view_box: None,
+        width: None,
+        height: None,
+    };
+    let mut in_root = false;
+
+    // Transforms of the currently opened elements, composed from the root
+    let mut transforms = vec![Transform::identity()];
+    // Number of opened elements inside a container which is not rendered
+    let mut hidden_depth = 0;
+
+    // Collect every path and basic shape in the file, in document order
+    for event in svg::read(content)? {
+        let (name, kind, attributes) = match event {
+            Event::Tag(name, kind, attributes) => (name, kind, attributes),
+            Event::Error(err) => return Err(Error::InvalidSvg(err.to_string())),
+            _ => continue,
+        };
+        if kind == Type::End {
+            if transforms.len() > 1 {
+                transforms.pop();
+            }
+            if hidden_depth > 0 {
+                hidden_depth -= 1;
+            }
+            continue;
+        }
+        if hidden_depth > 0 || NOT_RENDERED.contains(&name) {
+            if kind == Type::Start {
+                transforms.push(Transform::identity());
+                hidden_depth += 1;
+            }
+            continue;
+        }
+
+        // The element transform applies first, then the ones of its ancestors
+        let parent = *transforms.last().unwrap();
+        let transform = match attributes.get("transform") {
+            Some(value) => transform_attribute(value).then(&parent),
+            None => parent,
+        };
+        if kind == Type::Start {
+            transforms.push(transform);
+        }
+
+        // Only the outermost svg element sets the document size
+        if name == tag::SVG && !in_root {
+            in_root = true;
+            document.view_box = attributes.get("viewBox")
+                .and_then(|value| value.parse::<ViewBox>().ok())
+                .map(|v| rect(v.x as f32, v.y as f32, v.w as f32, v.h as f32));
+            document.width = absolute_length_attribute(&attributes, "width");
+            document.height = absolute_length_attribute(&attributes, "height");
+        }
+
+        // Like degenerate shapes, paths with nothing to draw are left out
+        if let Some(path) = build_path_from_element(name, &attributes)? {
+            let path = path.transformed(&transform);
+            if compute_path_length(&path) > 0.0 {
+                document.paths.push(path);
+            }
+        }
+    }
+    Ok(document)
+}
+
+fn build_path_from_element(name: &str, attributes: &Attributes) -> Result<Option<Path>, Error> {
+    let path = match name {
+        tag::Path => {
+            match attributes.get("d") {
+                Some(data) => Some(build_path_from_svg(data)?),
+                None => None,
+            }
+        }
+        tag::Rectangle => {
+            // A missing radius takes the value of the other one
+            let (rx, ry) = match (length_attribute(attributes, "rx"), length_attribute(attributes, "ry")) {
+                (Some(rx), Some(ry)) => (rx, ry),
+                (Some(r), None) | (None, Some(r)) => (r, r),
+                (None, None) => (0.0, 0.0),
+            };
+            build_path_from_rect(
+                length_attribute(attributes, "x").unwrap_or(0.0),
+                length_attribute(attributes, "y").unwrap_or(0.0),
+                length_attribute(attributes, "width").unwrap_or(0.0),
+                length_attribute(attributes, "height").unwrap_or(0.0),
+                rx, ry)
+        }
+        tag::Circle => {
+            let r = length_attribute(attributes, "r").unwrap_or(0.0);
+            build_path_from_ellipse(
+                length_attribute(attributes, "cx").unwrap_or(0.0),
+                length_attribute(attributes, "cy").unwrap_or(0.0),
+                r, r)
+        }
+        tag::Ellipse => {
+            build_path_from_ellipse(
+                length_attribute(attributes, "cx").unwrap_or(0.0),
+                length_attribute(attributes, "cy").unwrap_or(0.0),
+                length_attribute(attributes, "rx").unwrap_or(0.0),
+                length_attribute(attributes, "ry").unwrap_or(0.0))
+        }
+        tag::Line => {
+            let from = point(
+                length_attribute(attributes, "x1").unwrap_or(0.0),
+                length_attribute(attributes, "y1").unwrap_or(0.0));
+            let to = point(
+                length_attribute(attributes, "x2").unwrap_or(0.0),
+                length_attribute(attributes, "y2").unwrap_or(0.0));
+            build_path_from_points(&[from, to], false)
+        }
+        tag::Polyline | tag::Polygon => {
+            let points: Vec<_> = attributes.get("points")
+                .map(|points| PointsParser::from(&**points)
+                    .map(|(x, y)| point(x as f32, y as f32))
+                    .collect())
+                .unwrap_or_default();
+            build_path_from_points(&points, name == tag::Polygon)
+        }
+        _ => None
+    };
+    Ok(path)
+}
+
+// Units are ignored, lengths are taken as user units
+fn length_attribute(attributes: &Attributes, name: &str) -> Option<f32> {
+    attributes.get(name)
+        .and_then(|value| value.parse::<Length>().ok())
+        .map(|length| length.num as f32)
+}
+
+// Percentages depend on the embedding page, so they give no size
+fn absolute_length_attribute(attributes: &Attributes, name: &str) -> Option<f32> {
+    attributes.get(name)
+        .and_then(|value| value.parse::<Length>().ok())
+        .filter(|length| length.unit != LengthUnit::Percent && length.num > 0.0)
+        .map(|length| length.num as f32)
+}
+
+// Their content is only drawn when referenced, if ever
+const NOT_RENDERED: &[&str] = &["defs", "clipPath", "mask", "symbol", "marker", "pattern"];
+
+// Transform lists are composed into a single matrix, invalid ones are ignored
+fn transform_attribute(value: &str) -> Transform {
+    match value.parse::<svgtypes::Transform>() {
+        Ok(t) => Transform::new(t.a as f32, t.b as f32, t.c as f32, t.d as f32, t.e as f32, t.f as f32),
+        Err(_) => Transform::identity(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lyon::algorithms::aabb::bounding_rect;
+
+    #[test]
+    fn reads_the_document_frame() {
+        let document = read_svg_str("<svg width=\"100px\" height=\"50\" viewBox=\"-10 0 200 100\"></svg>").unwrap();
+        assert_eq!(document.frame(), Some(rect(-10.0, 0.0, 200.0, 100.0)));
+
+        let document = read_svg_str("<svg width=\"100\" height=\"50%\"></svg>").unwrap();
+        assert_eq!(document.frame(), None);
+    }
+
+    #[test]
+    fn composes_nested_transforms() {
+        let document = read_svg_str("<svg>
+            <g transform=\"translate(100 0)\">
+                <g transform=\"scale(2)\"><rect width=\"10\" height=\"10\" transform=\"translate(5)\"/></g>
+            </g>
+            <rect width=\"10\" height=\"10\"/>
+        </svg>").unwrap();
+        assert_eq!(document.paths.len(), 2);
+        assert_eq!(bounding_rect(document.paths[0].iter()), rect(110.0, 0.0, 20.0, 20.0));
+        assert_eq!(bounding_rect(document.paths[1].iter()), rect(0.0, 0.0, 10.0, 10.0));
+    }
+
+    #[test]
+    fn reads_basic_shapes() {
+        let document = read_svg_str("<svg>
+            <circle cx=\"10\" cy=\"10\" r=\"5\"/>
+            <ellipse rx=\"5\" ry=\"2\"/>
+            <line x2=\"10\"/>
+            <polyline points=\"0,0 10,0 10,10\"/>
+            <polygon points=\"0,0 10,0 10,10\"/>
+            <rect width=\"0\" height=\"10\"/>
+        </svg>").unwrap();
+        assert_eq!(document.paths.len(), 5);
+        assert_eq!(bounding_rect(document.paths[0].iter()), rect(5.0, 5.0, 10.0, 10.0));
+    }
+
+    #[test]
+    fn skips_the_shapes_which_are_not_rendered() {
+        let document = read_svg_str("<svg>
+            <defs>
+                <clipPath id=\"clip\"><rect width=\"100\" height=\"100\"/></clipPath>
+                <g><circle r=\"5\"/></g>
+                <linearGradient id=\"gradient\"/>
+            </defs>
+            <g clip-path=\"url(#clip)\" transform=\"translate(5)\"><rect width=\"10\" height=\"10\"/></g>
+            <symbol id=\"dot\"><circle r=\"1\"/></symbol>
+            <mask id=\"mask\"><path d=\"M 0 0 L 1 1\"/></mask>
+            <rect width=\"10\" height=\"10\"/>
+        </svg>").unwrap();
+        assert_eq!(document.paths.len(), 2);
+        assert_eq!(bounding_rect(document.paths[0].iter()), rect(5.0, 0.0, 10.0, 10.0));
+        assert_eq!(bounding_rect(document.paths[1].iter()), rect(0.0, 0.0, 10.0, 10.0));
+    }
+
+    #[test]
+    fn skips_the_paths_with_no_length() {
+        let document = read_svg_str("<svg>
+            <circle cx=\"50\" cy=\"50\" r=\"20\"/>
+            <line/>
+            <polyline points=\"5,5 5,5\"/>
+            <path d=\"\"/>
+            <path d=\"M 10 10\"/>
+            <rect width=\"10\" height=\"10\" transform=\"scale(0)\"/>
+        </svg>").unwrap();
+        assert_eq!(document.paths.len(), 1);
+        assert_eq!(bounding_rect(document.paths[0].iter()), rect(30.0, 30.0, 40.0, 40.0));
+    }
+
+    #[test]
+    fn reports_invalid_path_data() {
+        let result = read_svg_str("<svg><path d=\"M 0 0 L x\"/></svg>");
+        assert!(matches!(result, Err(Error::InvalidPathData { .. })));
+    }
+
+    #[test]
+    fn names_the_file_which_cannot_be_read() {
+        let err = read_svg_file("/nonexistent/drawing.svg").err().unwrap();
+        assert!(err.to_string().starts_with("/nonexistent/drawing.svg: "));
+    }
+}
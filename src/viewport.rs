@@ -0,0 +1,110 @@
+use lyon::algorithms::aabb::bounding_rect;
+use lyon::algorithms::fit::{fit_rectangle, FitStyle};
+use lyon_path::geom::euclid::default::Box2D;
+use lyon_path::math::{rect, Rect, Transform, Vector};
+use lyon_path::Path;
+
+use crate::error::Error;
+
+// Where the drawing goes on the output canvas
+#[derive(Clone, Debug)]
+pub struct Viewport {
+    pub bounds: Rect,
+    pub width: f32,
+    pub height: f32,
+    pub padding: f32,
+}
+
+impl Viewport {
+    pub fn new(bounds: Rect, width: f32, height: f32, padding: f32) -> Viewport {
+        Viewport {
+            bounds,
+            width,
+            height,
+            padding,
+        }
+    }
+
+    // An empty canvas has nothing to draw on, and a negative padding would crop the drawing
+    pub fn validate(&self) -> Result<(), Error> {
+        if !(self.width > 0.0 && self.width.is_finite()) {
+            return Err(Error::InvalidCanvas(format!("width {}, expected a positive number", self.width)));
+        }
+        if !(self.height > 0.0 && self.height.is_finite()) {
+            return Err(Error::InvalidCanvas(format!("height {}, expected a positive number", self.height)));
+        }
+        if !(self.padding >= 0.0 && self.padding.is_finite()) {
+            return Err(Error::InvalidCanvas(format!("padding {}, expected a positive number or zero", self.padding)));
+        }
+        Ok(())
+    }
+
+    // Uniformly scale the bounds into the canvas minus the padding, centered
+    pub fn transform(&self) -> Transform {
+        let mut bounds = self.bounds;
+        // Keep a flat drawing (e.g. a single line) from being scaled infinitely
+        if bounds.size.width <= 0.0 {
+            bounds.size.width = bounds.size.height.max(1.0);
+            bounds.origin.x -= bounds.size.width / 2.0;
+        }
+        if bounds.size.height <= 0.0 {
+            bounds.size.height = bounds.size.width.max(1.0);
+            bounds.origin.y -= bounds.size.height / 2.0;
+        }
+
+        let padding = self.padding.min(self.width / 2.0).min(self.height / 2.0);
+        let canvas = rect(padding, padding, self.width - 2.0 * padding, self.height - 2.0 * padding);
+        fit_rectangle(&bounds, &canvas, FitStyle::Min)
+    }
+
+    pub fn scale(&self) -> f32 {
+        self.transform().m11
+    }
+
+    pub fn offset(&self) -> Vector {
+        let transform = self.transform();
+        Vector::new(transform.m31, transform.m32)
+    }
+}
+
+// Flat paths have empty bounds, so they are merged by their corners
+pub fn paths_bounds(paths: &[Path]) -> Rect {
+    paths.iter()
+        .map(|path| bounding_rect(path.iter()).to_box2d())
+        .reduce(|a, b| Box2D::new(a.min.min(b.min), a.max.max(b.max)))
+        .map(|b| b.to_rect())
+        .unwrap_or_else(Rect::zero)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lyon_path::math::point;
+
+    #[test]
+    fn centers_and_scales_into_the_padded_canvas() {
+        let viewport = Viewport::new(rect(0.0, 0.0, 100.0, 50.0), 220.0, 220.0, 10.0);
+        assert!((viewport.scale() - 2.0).abs() < 1e-5);
+        let center = viewport.transform().transform_point(point(50.0, 25.0));
+        assert!((center - point(110.0, 110.0)).length() < 1e-3);
+    }
+
+    #[test]
+    fn merges_flat_paths_bounds() {
+        let horizontal = crate::path_util::build_path_from_svg("M 0 0 L 10 0").unwrap();
+        let vertical = crate::path_util::build_path_from_svg("M 20 5 L 20 15").unwrap();
+        let bounds = paths_bounds(&[horizontal, vertical]);
+        assert_eq!(bounds, rect(0.0, 0.0, 20.0, 15.0));
+        assert!(Viewport::new(rect(0.0, 0.0, 10.0, 0.0), 100.0, 100.0, 0.0).scale().is_finite());
+    }
+
+    #[test]
+    fn rejects_canvases_which_cannot_be_drawn_on() {
+        let bounds = rect(0.0, 0.0, 10.0, 10.0);
+        assert!(Viewport::new(bounds, 100.0, 100.0, 0.0).validate().is_ok());
+        for (width, height, padding) in [(0.0, 100.0, 0.0), (100.0, -1.0, 0.0), (100.0, 100.0, -1.0), (f32::NAN, 100.0, 0.0)] {
+            let viewport = Viewport::new(bounds, width, height, padding);
+            assert!(matches!(viewport.validate(), Err(Error::InvalidCanvas(_))));
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use std::fmt::Write;
+
+use crate::error::Error;
+use crate::visualizer::{Visualizer, RenderOptions, Output};
+use crate::fft_drawer::Drawing;
+use crate::path_util::sample_path;
+
+// A row per wave, for spreadsheets and data frames
+pub struct CsvVisualizer {
+    output: Output,
+}
+
+impl CsvVisualizer {
+    pub fn new(output: Output) -> CsvVisualizer {
+        CsvVisualizer{
+            output,
+        }
+    }
+}
+
+impl Visualizer for CsvVisualizer {
+    fn render(&self, drawings: &[Drawing], _options: &RenderOptions) -> Result<(), Error> {
+        let mut content = String::from("drawing,frequency,radius,angle,re,im\n");
+        for (i, drawing) in drawings.iter().enumerate() {
+            for d in &drawing.waves {
+                writeln!(content, "{},{},{},{},{},{}",
+                    i, d.frequency, d.radius, d.angle, d.radius * d.angle.cos(), d.radius * d.angle.sin()).unwrap();
+            }
+        }
+        self.output.write(content.as_bytes())
+    }
+}
+
+// The points sampled along the original paths, which the waves were computed from
+pub fn write_samples_csv(drawings: &[Drawing], output: &Output) -> Result<(), Error> {
+    let mut content = String::from("drawing,index,x,y\n");
+    for (i, drawing) in drawings.iter().enumerate() {
+        let original = match &drawing.original {
+            Some(original) => original,
+            None => continue,
+        };
+        for (j, sample) in sample_path(original, drawing.sample_count)?.iter().enumerate() {
+            writeln!(content, "{},{},{},{}", i, j, sample.re, sample.im).unwrap();
+        }
+    }
+    output.write(content.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fft_drawer::DrawData;
+    use crate::path_util::build_path_from_svg;
+    use crate::viewport::Viewport;
+    use crate::paths_to_drawings;
+    use lyon_path::math::rect;
+    use std::f32::consts::PI;
+
+    #[test]
+    fn writes_a_row_per_wave() {
+        let output = Output::memory();
+        let visualizer = CsvVisualizer::new(output.clone());
+        let options = RenderOptions::new(Viewport::new(rect(0.0, 0.0, 10.0, 10.0), 100.0, 100.0, 0.0));
+        let drawings = vec![
+            Drawing::new(vec![DrawData::new(0.0, 5.0, 0.0)], None),
+            Drawing::new(vec![DrawData::new(-1.0, 2.0, PI / 2.0)], None),
+        ];
+        visualizer.render(&drawings, &options).unwrap();
+
+        let content = String::from_utf8(output.contents().unwrap()).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines[0], "drawing,frequency,radius,angle,re,im");
+        assert_eq!(lines[1], "0,0,5,0,5,0");
+        assert!(lines[2].starts_with("1,-1,2,1.5707964,"));
+    }
+
+    #[test]
+    fn dumps_the_sampled_points() {
+        let output = Output::memory();
+        let path = build_path_from_svg("M 0 0 L 10 0 L 10 10 L 0 10 Z").unwrap();
+        let drawings = paths_to_drawings(vec![path], 4, 3).unwrap();
+        write_samples_csv(&drawings, &output).unwrap();
+
+        let content = String::from_utf8(output.contents().unwrap()).unwrap();
+        assert_eq!(content, "drawing,index,x,y\n0,0,0,0\n0,1,10,0\n0,2,10,10\n0,3,0,10\n");
+    }
+}
@@ -0,0 +1,115 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+
+use color_quant::NeuQuant;
+use gif::{Encoder, Frame, Repeat};
+use tiny_skia::Pixmap;
+
+use crate::error::Error;
+use crate::visualizer::{Visualizer, RenderOptions, Output};
+use crate::visualizer::raster::FrameRasterizer;
+use crate::fft_drawer::Drawing;
+
+// Frames used to choose the palette colors
+const PALETTE_SAMPLE_FRAMES: usize = 8;
+
+// Looping animated GIF, like the preview of the README
+pub struct GifVisualizer {
+    output: Output,
+}
+
+impl GifVisualizer {
+    pub fn new(output: Output) -> GifVisualizer {
+        GifVisualizer{
+            output,
+        }
+    }
+}
+
+impl Visualizer for GifVisualizer {
+    fn render(&self, drawings: &[Drawing], options: &RenderOptions) -> Result<(), Error> {
+        options.validate()?;
+        let rasterizer = FrameRasterizer::new(drawings, options);
+        let width = u16::try_from(rasterizer.width())
+            .map_err(|_| Error::Render("GIF images are at most 65535 pixels wide".to_string()))?;
+        let height = u16::try_from(rasterizer.height())
+            .map_err(|_| Error::Render("GIF images are at most 65535 pixels high".to_string()))?;
+        let frames = options.video_frames().max(1);
+
+        // A single palette for the whole animation, learned from a few frames
+        let mut samples = Vec::new();
+        for i in 0..PALETTE_SAMPLE_FRAMES.min(frames) {
+            let frame = i * frames / PALETTE_SAMPLE_FRAMES.min(frames);
+            samples.extend(rgba(&rasterizer.render(options.video_frame_time(frame))?));
+        }
+        // The quantizer needs at least 64 colors
+        let quantizer = NeuQuant::new(10, options.palette_size, &samples);
+
+        let mut writer = self.output.writer()?;
+        {
+            let mut encoder = Encoder::new(&mut writer, width, height, &quantizer.color_map_rgb())
+                .map_err(|err| Error::Render(err.to_string()))?;
+            encoder.set_repeat(Repeat::Infinite).map_err(|err| Error::Render(err.to_string()))?;
+
+            // GIF delays are in hundredths of a second
+            let delay = (100.0 / options.fps).round().max(1.0) as u16;
+            let mut indices = HashMap::new();
+            for i in 0..frames {
+                let pixels = rgba(&rasterizer.render(options.video_frame_time(i))?);
+                let buffer: Vec<u8> = pixels.chunks_exact(4)
+                    .map(|pixel| *indices.entry([pixel[0], pixel[1], pixel[2], pixel[3]])
+                        .or_insert_with(|| quantizer.index_of(pixel) as u8))
+                    .collect();
+                let frame = Frame {
+                    width,
+                    height,
+                    delay,
+                    buffer: Cow::Owned(buffer),
+                    ..Frame::default()
+                };
+                encoder.write_frame(&frame).map_err(|err| Error::Render(err.to_string()))?;
+            }
+        }
+        writer.flush().map_err(|err| self.output.io_error(err))?;
+        Ok(())
+    }
+}
+
+// Pixmaps store premultiplied colors
+pub(crate) fn rgba(pixmap: &Pixmap) -> Vec<u8> {
+    pixmap.pixels().iter()
+        .flat_map(|pixel| {
+            let color = pixel.demultiply();
+            [color.red(), color.green(), color.blue(), color.alpha()]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::visualizer::{test_drawings, test_options};
+
+    #[test]
+    fn encodes_a_looping_animation() {
+        let output = Output::memory();
+        let visualizer = GifVisualizer::new(output.clone());
+        let mut options = test_options(32.0, 24.0);
+        options.fps = 10.0;
+        options.duration = Some(0.5);
+        let drawings = test_drawings();
+        visualizer.render(&drawings, &options).unwrap();
+
+        let content = output.contents().unwrap();
+        assert_eq!(&content[..6], b"GIF89a");
+        let mut decoder = gif::DecodeOptions::new().read_info(&content[..]).unwrap();
+        assert_eq!((decoder.width(), decoder.height()), (32, 24));
+        let mut frames = 0;
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            assert_eq!(frame.delay, 10);
+            frames += 1;
+        }
+        assert_eq!(frames, 5);
+    }
+}
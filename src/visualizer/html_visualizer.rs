@@ -1,43 +1,77 @@
-use std::fs;
-use std::io::Error;
+use serde::Serialize;
 
-use crate::visualizer::Visualizer;
+use crate::coefficients::Bounds;
+use crate::error::Error;
+use crate::visualizer::{Visualizer, RenderOptions, Output};
 use crate::fft_drawer;
+use crate::path_util::path_to_svg;
 
 pub struct HTMLVisualizer {
-    file_name: String,
+    output: Output,
 }
 
 impl HTMLVisualizer {
-    pub fn new(file_name: String) -> HTMLVisualizer {
+    pub fn new(output: Output) -> HTMLVisualizer {
         HTMLVisualizer{
-            file_name: file_name,
+            output,
         }
     }
 }
 
+// What the page needs of a drawing, the waves kept in the order they were selected
+#[derive(Serialize)]
+struct PageDrawing<'a> {
+    waves: &'a [fft_drawer::DrawData],
+    original: Option<String>,
+    bounds: Option<Bounds>,
+}
+
 impl Visualizer for HTMLVisualizer {
-    fn render(&self, data: Vec<fft_drawer::DrawData>) -> bool {
-        let fourier_json_data: String = data.iter()
-            .map(|d| format!("{{\"s\": {:?}, \"r\": {:?}, \"a\": {:?}}},", d.frequency, d.radius, d.angle))
+    fn render(&self, drawings: &[fft_drawer::Drawing], options: &RenderOptions) -> Result<(), Error> {
+        options.validate()?;
+        let page_drawings: Vec<PageDrawing> = drawings.iter()
+            .map(|drawing| PageDrawing {
+                waves: &drawing.waves,
+                original: drawing.original.as_ref().map(path_to_svg),
+                bounds: drawing.bounds.map(Bounds::from),
+            })
             .collect();
-        // Strip the last comma
-        let final_fourier_json_data: &str;
-        if fourier_json_data.len() > 1 {
-            final_fourier_json_data = &fourier_json_data[0..fourier_json_data.len() - 1];
-        } else {
-            final_fourier_json_data = &fourier_json_data;
-        }
+        let fourier_json_data = serde_json::to_string(&page_drawings).map_err(|err| Error::Render(err.to_string()))?;
+        let viewport = &options.viewport;
+        let scale = viewport.scale();
+        let offset = viewport.offset();
+        let theme = &options.theme;
+        let trail = theme.trail_color;
+        let trail_end = theme.trail_color_at(1.0);
+        // Keep the controls readable on dark backgrounds
+        let background = theme.background;
+        let luminance = 0.299 * background.r as f32 + 0.587 * background.g as f32 + 0.114 * background.b as f32;
+        let control_color = if luminance < 128.0 { "#eeeeee" } else { "#222222" };
         let content = format!("<html>
 <head>
     <title>Fourier Visualizer</title>
 </head>
-<canvas id=\"fourier_canvas\" width=\"800\" height=\"600\"></canvas>
+<body style=\"margin: 0; background: {background};\">
+<div id=\"controls\" style=\"font: 13px sans-serif; padding: 6px; color: {control_color};\">
+    <button id=\"play\">Pause</button>
+    <label>Circles <input id=\"circles\" type=\"range\" min=\"1\" max=\"1\" value=\"1\"> <span id=\"circles_value\"></span></label>
+    <label>Speed <input id=\"speed\" type=\"range\" min=\"-3\" max=\"3\" step=\"0.1\" value=\"0\"> <span id=\"speed_value\">1.00x</span></label>
+    <label><input id=\"original\" type=\"checkbox\"{original_checked}> Original path</label>
+    <button id=\"reset_view\">Reset view</button>
+</div>
+<canvas id=\"fourier_canvas\" width=\"{width}\" height=\"{height}\"></canvas>
 <script>
 /* FROM FourierFromSVG project */
-let canvas = null; 
+let canvas = null;
 let context = null;
+// Counted in revolutions, only while playing
 let time = 0;
+let last_timestamp = null;
+let playing = true;
+// How many times faster than the period the revolutions go
+let speed = 1;
+// Zoom and pan, on top of the mapping of the drawing onto the canvas
+let view = {{ zoom: 1, x: 0, y: 0 }};
 const Point = class {{
     constructor(x, y) {{
         this.x = x;
@@ -52,112 +86,353 @@ const Point = class {{
 }};
 
 const FourierCircle = class {{
-    constructor(speed, radius, initial_angle)
+    constructor(frequency, radius, initial_angle)
     {{
-        this.radius = radius/2;
-        this.speed = speed/20;
+        this.radius = radius * scale;
+        this.frequency = frequency;
         this.initial_angle = initial_angle
     }}
-    draw(ctx, at) 
+    draw(ctx, at)
     {{
+        if (this.radius < min_arm_radius)
+            return;
+        if (show_circles) {{
+            ctx.beginPath();
+            ctx.arc(at.x, at.y, this.radius, 0, Math.PI * 2, true);
+            ctx.strokeStyle = '{circle_color}';
+            ctx.lineWidth = arm_width / view.zoom;
+            ctx.stroke();
+        }}
         ctx.beginPath();
-        //ctx.arc(at.x, at.y, this.radius, 0, Math.PI * 2, true);
-        var x = at.x + this.radius * Math.cos(this.initial_angle + 2 * Math.PI * time * this.speed);
-        var y = at.y + this.radius * Math.sin(this.initial_angle + 2 * Math.PI * time * this.speed);
+        var x = at.x + this.radius * Math.cos(this.initial_angle + 2 * Math.PI * time * this.frequency);
+        var y = at.y + this.radius * Math.sin(this.initial_angle + 2 * Math.PI * time * this.frequency);
         ctx.moveTo(at.x, at.y);
         ctx.lineTo(x, y);
         ctx.closePath();
-        ctx.strokeStyle = 'rgba(202, 126, 86, 0.7)';
-        ctx.lineWidth = 1; 
+        ctx.strokeStyle = '{arm_color}';
+        ctx.lineWidth = arm_width / view.zoom;
         ctx.stroke();
     }}
 
-    nextCenter(at) 
+    nextCenter(at)
     {{
-        var x = at.x + this.radius * Math.cos(this.initial_angle + 2 * Math.PI * time * this.speed);
-        var y = at.y + this.radius * Math.sin(this.initial_angle + 2 * Math.PI * time * this.speed);
+        var x = at.x + this.radius * Math.cos(this.initial_angle + 2 * Math.PI * time * this.frequency);
+        var y = at.y + this.radius * Math.sin(this.initial_angle + 2 * Math.PI * time * this.frequency);
         return new Point(x, y)
     }}
 }};
 
-let n;
-let circles;
+// The embedded waves and original paths, truncated into the drawings
+let data;
+// Every path of the drawing has its own chain of circles and its own curve
+let drawings;
 let animation_id = 0;
-let center = new Point(150, 150);
-let wave = [];
+// Map the drawing onto the canvas
+const scale = {scale:?};
+let center = new Point({offset_x:?}, {offset_y:?});
 
-function init_fourier(canvas_elm, constants, count) {{
+const background = '{background}';
+const arm_width = {arm_width:?};
+const trail_width = {trail_width:?};
+// The trail goes from the first color at the tip to the second one at its end
+const trail_color = [{trail_r}, {trail_g}, {trail_b}, {trail_a:?}];
+const trail_end_color = [{trail_end_r}, {trail_end_g}, {trail_end_b}, {trail_end_a:?}];
+const original_color = '{original_color}';
+let show_original = {show_original};
+// Seconds taken by a revolution
+const period = {period:?};
+const show_circles = {show_circles};
+const min_arm_radius = {min_arm_radius:?};
+const show_tip = {show_tip};
+const sort_by_radius = {sort_by_radius};
+// Portion of a revolution covered by the trail
+const trail_length = {trail_length:?};
+// The trail fades in steps, each one stroked at once
+const trail_steps = 64;
+
+function init_fourier(canvas_elm, fourier_data) {{
     canvas = canvas_elm;
     context = canvas.getContext('2d');
     if(animation_id !== 0)
         window.cancelAnimationFrame(animation_id);
-    n = count;
-    circles = [];
-    wave = [];
-    for (let i = 0; i < count; i++) {{
-        let constant = constants[i];
-        circles[i] = new FourierCircle(constant.s, constant.r, constant.a);
-    }}
+    data = fourier_data;
+    let wave_count = Math.max(1, ...data.map(drawing => drawing.waves.length));
+    let circles_input = document.getElementById('circles');
+    circles_input.max = wave_count;
+    circles_input.value = wave_count;
+    set_wave_count(wave_count);
+    last_timestamp = null;
     animation_id = window.requestAnimationFrame(draw);
 }}
 
-function draw_wave(ctx) {{
-    // ctx.beginPath();
-    for (let i = 1; i < wave.length; i++) {{
+// Keep the first waves as they were selected, then chain their arms
+function set_wave_count(count) {{
+    drawings = [];
+    for (let drawing of data) {{
+        let constants = drawing.waves.slice(0, count);
+        // The first wave does not rotate and stays first
+        let chained = constants.slice(1);
+        if (sort_by_radius)
+            chained.sort((a, b) => b.radius - a.radius);
+        chained = constants.slice(0, 1).concat(chained);
+        let circles = [];
+        for (let i = 0; i < chained.length; i++) {{
+            let constant = chained[i];
+            circles[i] = new FourierCircle(constant.frequency, constant.radius, constant.angle);
+        }}
+        drawings.push({{
+            circles: circles,
+            curve: compute_curve(constants),
+            original: drawing.original === null ? null : new Path2D(drawing.original),
+            bounds: drawing.bounds,
+        }});
+    }}
+    document.getElementById('circles_value').textContent = count;
+}}
+
+// The closed curve along a revolution, with enough points for the highest frequency
+function compute_curve(constants) {{
+    let max_frequency = Math.max(0, ...constants.map(constant => Math.abs(constant.frequency)));
+    let n = Math.min(Math.max(max_frequency * 8, 512), 8192);
+    let curve = [];
+    for (let i = 0; i < n; i++) {{
+        let x = center.x;
+        let y = center.y;
+        for (let constant of constants) {{
+            let angle = constant.angle + 2 * Math.PI * constant.frequency * i / n;
+            x += constant.radius * scale * Math.cos(angle);
+            y += constant.radius * scale * Math.sin(angle);
+        }}
+        curve.push(new Point(x, y));
+    }}
+    return curve;
+}}
+
+// From the tip, going back along the curve
+function draw_trail(ctx, curve, tip) {{
+    let n = curve.length;
+    let newest = Math.floor(time * n);
+    let trail = [tip];
+    for (let k = 0; k < Math.round(trail_length * n); k++) {{
+        trail.push(curve[(((newest - k) % n) + n) % n]);
+    }}
+
+    let step_size = Math.ceil(trail.length / trail_steps);
+    for (let start = 0; start < trail.length - 1; start += step_size) {{
+        let end = Math.min(start + step_size, trail.length - 1);
         ctx.beginPath();
-        ctx.moveTo(wave[i-1].x, wave[i-1].y);
-        ctx.lineTo(wave[i].x, wave[i].y);
-        ctx.closePath();
+        ctx.moveTo(trail[start].x, trail[start].y);
+        for (let i = start + 1; i <= end; i++) {{
+            ctx.lineTo(trail[i].x, trail[i].y);
+        }}
 
-        // let c = Math.ceil(127.0 + 128.0*i/wave.length);
-        let alpha = 1 - i*1.0/wave.length;
-        
-        ctx.strokeStyle = 'rgba(0, 0, 0, ' + alpha + ')';
-        //ctx.strokeStyle = 'rgba(0, 0, 0, 1)';
-        ctx.lineWidth = 1;
+        // Older parts of the trail turn into the end color
+        let age = start / trail.length;
+        let c = trail_color.map((value, k) => value + (trail_end_color[k] - value) * age);
+        ctx.strokeStyle = 'rgba(' + Math.round(c[0]) + ', ' + Math.round(c[1]) + ', ' + Math.round(c[2]) + ', ' + c[3] + ')';
+        ctx.lineWidth = trail_width / view.zoom;
         ctx.stroke();
     }}
-    // ctx.closePath();
-    // ctx.strokeStyle = 'rgba(0, 0, 0, 1)';
-    // ctx.stroke();
 }}
 
-function draw() {{
+// The path is in the units of the drawing, mapped like the waves
+function draw_original(ctx, original, bounds) {{
+    // Nothing to stroke when the path is out of the view
+    let left = (center.x + bounds.x * scale) * view.zoom + view.x;
+    let top = (center.y + bounds.y * scale) * view.zoom + view.y;
+    let right = left + bounds.width * scale * view.zoom;
+    let bottom = top + bounds.height * scale * view.zoom;
+    if (right < 0 || bottom < 0 || left > canvas.width || top > canvas.height)
+        return;
+    ctx.save();
+    ctx.transform(scale, 0, 0, scale, center.x, center.y);
+    ctx.strokeStyle = original_color;
+    ctx.lineWidth = trail_width / (scale * view.zoom);
+    ctx.stroke(original);
+    ctx.restore();
+}}
+
+function draw(timestamp) {{
+    // Follow the clock, whatever the refresh rate of the screen
+    if (last_timestamp !== null && playing)
+        time += (timestamp - last_timestamp) / 1000 / period * speed;
+    last_timestamp = timestamp;
+
+    context.setTransform(1, 0, 0, 1, 0, 0);
     context.clearRect(0,0, canvas.width, canvas.height);
-    // let new_center = center;
-    let new_center = circles[0].nextCenter(center);
-    for(let i = 1; i < n; i++) {{
-        circles[i].draw(context, new_center);
-        new_center = circles[i].nextCenter(new_center);
+    context.fillStyle = background;
+    context.fillRect(0, 0, canvas.width, canvas.height);
+    context.setTransform(view.zoom, 0, 0, view.zoom, view.x, view.y);
+    if (show_original) {{
+        for (let drawing of drawings) {{
+            if (drawing.original !== null)
+                draw_original(context, drawing.original, drawing.bounds);
+        }}
+    }}
+    for (let drawing of drawings) {{
+        let circles = drawing.circles;
+        if (circles.length === 0)
+            continue;
+        // let new_center = center;
+        let new_center = circles[0].nextCenter(center);
+        for(let i = 1; i < circles.length; i++) {{
+            circles[i].draw(context, new_center);
+            new_center = circles[i].nextCenter(new_center);
+        }}
+
+        draw_trail(context, drawing.curve, new_center);
+        if (show_tip) {{
+            context.beginPath();
+            context.arc(new_center.x, new_center.y, Math.max(3, 2 * arm_width) / view.zoom, 0, Math.PI * 2, true);
+            context.fillStyle = 'rgba(' + trail_color.join(', ') + ')';
+            context.fill();
+        }}
     }}
-    
-    wave.unshift(new_center);
-    draw_wave(context);
 
     animation_id = window.requestAnimationFrame(draw);
+}}
 
-    time += 0.04;
-    if(wave.length > 400) {{
-        wave.pop();
-    }}
+function init_controls() {{
+    let play = document.getElementById('play');
+    play.addEventListener('click', function() {{
+        playing = !playing;
+        play.textContent = playing ? 'Pause' : 'Play';
+    }});
+    let circles = document.getElementById('circles');
+    circles.addEventListener('input', function() {{
+        set_wave_count(parseInt(circles.value));
+    }});
+    // From an eighth of the pace to eight times it
+    let speed_input = document.getElementById('speed');
+    speed_input.addEventListener('input', function() {{
+        speed = Math.pow(2, parseFloat(speed_input.value));
+        document.getElementById('speed_value').textContent = speed.toFixed(2) + 'x';
+    }});
+    let original = document.getElementById('original');
+    original.addEventListener('change', function() {{
+        show_original = original.checked;
+    }});
+    document.getElementById('reset_view').addEventListener('click', function() {{
+        view = {{ zoom: 1, x: 0, y: 0 }};
+    }});
+
+    // Zoom around the pointer with the wheel, pan by dragging
+    canvas.addEventListener('wheel', function(event) {{
+        event.preventDefault();
+        let factor = Math.exp(-event.deltaY * 0.001);
+        view.x = event.offsetX - (event.offsetX - view.x) * factor;
+        view.y = event.offsetY - (event.offsetY - view.y) * factor;
+        view.zoom *= factor;
+    }}, {{ passive: false }});
+    let drag = null;
+    canvas.addEventListener('mousedown', function(event) {{
+        drag = new Point(event.clientX, event.clientY);
+    }});
+    window.addEventListener('mousemove', function(event) {{
+        if (drag === null)
+            return;
+        view.x += event.clientX - drag.x;
+        view.y += event.clientY - drag.y;
+        drag = new Point(event.clientX, event.clientY);
+    }});
+    window.addEventListener('mouseup', function() {{
+        drag = null;
+    }});
 }}
 /* GEN */
 window.onload = function() {{
     canvas = document.getElementById(\"fourier_canvas\");
-    let data = JSON.parse(`[{}]`);
-    init_fourier(canvas, data, {:?});
+    let data = {data};
+    init_fourier(canvas, data);
+    init_controls();
 }};
 </script>
-</html>", final_fourier_json_data, data.len());
-
-        let save_to_file = |file_name: &str| -> Result<(), Error> {
-            fs::write(file_name, content)?;
-            Ok(())
-        };
-        if let Err(_err) = save_to_file(&self.file_name) {
-            return false;
-        }
+</body>
+</html>",
+            width = viewport.width,
+            height = viewport.height,
+            control_color = control_color,
+            original_checked = if options.show_original { " checked" } else { "" },
+            arm_color = theme.arm_color,
+            scale = scale,
+            offset_x = offset.x,
+            offset_y = offset.y,
+            background = theme.background,
+            arm_width = theme.arm_width,
+            trail_width = theme.trail_width,
+            trail_r = trail.r,
+            trail_g = trail.g,
+            trail_b = trail.b,
+            trail_a = trail.a,
+            trail_end_r = trail_end.r,
+            trail_end_g = trail_end.g,
+            trail_end_b = trail_end.b,
+            trail_end_a = trail_end.a,
+            original_color = theme.original_color,
+            show_original = options.show_original,
+            period = options.period,
+            circle_color = theme.circle_color,
+            show_circles = options.show_circles,
+            min_arm_radius = options.min_arm_radius,
+            show_tip = options.show_tip,
+            sort_by_radius = options.sort_by_radius,
+            trail_length = options.trail_length,
+            data = fourier_json_data);
+
+        self.output.write(content.as_bytes())
+    }
+}
 
-        true
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fft_drawer::{DrawData, Drawing};
+    use crate::path_util::build_path_from_svg;
+    use crate::viewport::Viewport;
+    use lyon_path::math::rect;
+
+    #[test]
+    fn writes_the_waves_into_the_page() {
+        let output = Output::memory();
+        let visualizer = HTMLVisualizer::new(output.clone());
+        let options = RenderOptions::new(Viewport::new(rect(0.0, 0.0, 10.0, 10.0), 100.0, 80.0, 0.0));
+        let drawings = vec![Drawing::new(vec![DrawData::new(0.0, 5.0, 0.0), DrawData::new(1.0, 2.0, 0.5)], None)];
+        visualizer.render(&drawings, &options).unwrap();
+
+        let content = String::from_utf8(output.contents().unwrap()).unwrap();
+        assert!(content.contains("width=\"100\" height=\"80\""));
+        assert!(content.contains("const period = 8.0;"));
+        assert!(content.contains("[{\"waves\":[{\"frequency\":0.0,\"radius\":5.0,\"angle\":0.0},{\"frequency\":1.0,\"radius\":2.0,\"angle\":0.5}],\"original\":null,\"bounds\":null}]"));
+    }
+
+    #[test]
+    fn reports_write_failures() {
+        let visualizer = HTMLVisualizer::new(Output::from_arg("/nonexistent/output.html"));
+        let options = RenderOptions::new(Viewport::new(rect(0.0, 0.0, 10.0, 10.0), 100.0, 80.0, 0.0));
+        let err = visualizer.render(&[], &options).unwrap_err();
+        assert!(matches!(err, Error::Io { .. }));
+        assert!(err.to_string().starts_with("/nonexistent/output.html: "));
+    }
+
+    #[test]
+    fn embeds_what_the_controls_need() {
+        let output = Output::memory();
+        let visualizer = HTMLVisualizer::new(output.clone());
+        let mut options = RenderOptions::new(Viewport::new(rect(0.0, 0.0, 10.0, 10.0), 100.0, 80.0, 0.0));
+        options.sort_by_radius = true;
+        options.show_original = true;
+        let waves = vec![DrawData::new(0.0, 1.0, 0.0), DrawData::new(1.0, 2.0, 0.0), DrawData::new(-1.0, 3.0, 0.0)];
+        let original = build_path_from_svg("M 0 0 L 10 0 L 10 10 Z").unwrap();
+        visualizer.render(&[Drawing::new(waves, Some(original))], &options).unwrap();
+
+        let content = String::from_utf8(output.contents().unwrap()).unwrap();
+        // The waves keep their order to be truncated, the page sorts the arms
+        let radii: Vec<usize> = ["\"radius\":1.0", "\"radius\":2.0", "\"radius\":3.0"].iter()
+            .map(|radius| content.find(radius).unwrap())
+            .collect();
+        assert!(radii[0] < radii[1] && radii[1] < radii[2]);
+        assert!(content.contains("const sort_by_radius = true;"));
+        assert!(content.contains("\"original\":\"M0 0 L10 0 L10 10 Z\",\"bounds\":{\"x\":0.0,\"y\":0.0,\"width\":10.0,\"height\":10.0}"));
+        assert!(content.contains("<input id=\"original\" type=\"checkbox\" checked>"));
     }
 }
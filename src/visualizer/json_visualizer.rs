@@ -0,0 +1,24 @@
+use crate::coefficients::CoefficientsFile;
+use crate::error::Error;
+use crate::visualizer::{Visualizer, RenderOptions, Output};
+use crate::fft_drawer::Drawing;
+
+// Saves the waves in a coefficient file, to be drawn again later
+pub struct JsonVisualizer {
+    output: Output,
+}
+
+impl JsonVisualizer {
+    pub fn new(output: Output) -> JsonVisualizer {
+        JsonVisualizer{
+            output,
+        }
+    }
+}
+
+impl Visualizer for JsonVisualizer {
+    fn render(&self, drawings: &[Drawing], options: &RenderOptions) -> Result<(), Error> {
+        let file = CoefficientsFile::new(drawings, options.viewport.bounds);
+        self.output.write(format!("{}\n", file.to_json()).as_bytes())
+    }
+}
@@ -1,7 +1,349 @@
-use crate::fft_drawer;
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, MutexGuard};
 
+use lyon_path::math::{Point, Transform};
+use lyon_svg::parser;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::error::Error;
+use crate::fft_drawer::{self, DrawData};
+use crate::viewport::Viewport;
+use crate::visualizer::theme::Theme;
+
+pub mod csv_visualizer;
+pub mod gif_visualizer;
 pub mod html_visualizer;
+pub mod json_visualizer;
+pub mod png_visualizer;
+pub mod raster;
+pub mod registry;
+pub mod static_svg_visualizer;
+pub mod svg_visualizer;
+pub mod theme;
+pub mod y4m_visualizer;
 
 pub trait Visualizer {
-    fn render(&self, data: Vec<fft_drawer::DrawData>) -> bool;
+    fn render(&self, drawings: &[fft_drawer::Drawing], options: &RenderOptions) -> Result<(), Error>;
+}
+
+// Where a visualizer writes its result
+#[derive(Clone, Debug)]
+pub enum Output {
+    File(PathBuf),
+    Stdout,
+    // Kept in memory, shared with the clones of the output to read it back after rendering
+    Memory(Arc<Mutex<Vec<u8>>>),
+}
+
+impl Output {
+    // "-" stands for the standard output
+    pub fn from_arg(arg: &str) -> Output {
+        if arg == "-" {
+            Output::Stdout
+        } else {
+            Output::File(PathBuf::from(arg))
+        }
+    }
+
+    pub fn memory() -> Output {
+        Output::Memory(Arc::new(Mutex::new(Vec::new())))
+    }
+
+    // What was written into memory, None for the other outputs
+    pub fn contents(&self) -> Option<Vec<u8>> {
+        match self {
+            Output::Memory(buffer) => Some(lock(buffer).clone()),
+            _ => None,
+        }
+    }
+
+    pub fn writer(&self) -> Result<Box<dyn Write>, Error> {
+        match self {
+            Output::File(path) => {
+                let file = fs::File::create(path).map_err(|err| Error::io(path, err))?;
+                Ok(Box::new(io::BufWriter::new(file)))
+            }
+            Output::Stdout => Ok(Box::new(io::stdout())),
+            Output::Memory(buffer) => {
+                // Written again from the start, like a file
+                lock(buffer).clear();
+                Ok(Box::new(MemoryWriter(Arc::clone(buffer))))
+            }
+        }
+    }
+
+    pub fn write(&self, content: &[u8]) -> Result<(), Error> {
+        let mut writer = self.writer()?;
+        writer.write_all(content).map_err(|err| self.io_error(err))?;
+        writer.flush().map_err(|err| self.io_error(err))?;
+        Ok(())
+    }
+
+    // Names the file the error happened on
+    pub fn io_error(&self, source: io::Error) -> Error {
+        match self {
+            Output::File(path) => Error::io(path, source),
+            _ => Error::from(source),
+        }
+    }
+}
+
+// A writer panicking halfway leaves the bytes it wrote, still worth reading
+fn lock(buffer: &Mutex<Vec<u8>>) -> MutexGuard<'_, Vec<u8>> {
+    buffer.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+struct MemoryWriter(Arc<Mutex<Vec<u8>>>);
+
+impl Write for MemoryWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        lock(&self.0).extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: f32,
+}
+
+impl Color {
+    pub fn rgba(r: u8, g: u8, b: u8, a: f32) -> Color {
+        Color { r, g, b, a }
+    }
+}
+
+// Formatted as a CSS color
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "rgba({}, {}, {}, {})", self.r, self.g, self.b, self.a)
+    }
+}
+
+// SVG colors, with the alpha of CSS rgba() and #rrggbbaa
+impl FromStr for Color {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Color, Error> {
+        let s = s.trim();
+        let invalid = || Error::InvalidColor(s.to_string());
+        if let Some(arguments) = s.strip_prefix("rgba(").and_then(|rest| rest.strip_suffix(')')) {
+            let parts: Vec<&str> = arguments.split(',').map(str::trim).collect();
+            if parts.len() != 4 {
+                return Err(invalid());
+            }
+            let channel = |part: &str| part.parse::<u8>().map_err(|_| invalid());
+            let alpha = parts[3].parse::<f32>().map_err(|_| invalid())?;
+            return Ok(Color::rgba(channel(parts[0])?, channel(parts[1])?, channel(parts[2])?, alpha));
+        }
+        if s.len() == 9 && s.starts_with('#') {
+            let value = u32::from_str_radix(&s[1..], 16).map_err(|_| invalid())?;
+            let [r, g, b, a] = value.to_be_bytes();
+            return Ok(Color::rgba(r, g, b, a as f32 / 255.0));
+        }
+        let color = parser::Color::from_str(s).map_err(|_| invalid())?;
+        Ok(Color::rgba(color.red, color.green, color.blue, 1.0))
+    }
+}
+
+// Written as CSS colors in theme files
+impl Serialize for Color {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Color {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+// Shared by all the visualizers, each one uses what makes sense for its output
+#[derive(Clone, Debug)]
+pub struct RenderOptions {
+    pub viewport: Viewport,
+    pub theme: Theme,
+    // Seconds taken by a revolution
+    pub period: f32,
+    // Portion of a revolution covered by the fading trail
+    pub trail_length: f32,
+    // Also draw the curve with only the first waves, for each of these counts
+    pub wave_counts: Vec<usize>,
+    // Draw the path the waves were computed from under the result
+    pub show_original: bool,
+    // Number of images drawn along a revolution, for the image sequences
+    pub frames: usize,
+    // Smooth the edges of raster images
+    pub antialias: bool,
+    // Frames per second of the videos
+    pub fps: f32,
+    // Length of the videos in seconds, replacing the revolutions
+    pub duration: Option<f32>,
+    // Length of the videos in revolutions
+    pub revolutions: f32,
+    // Number of colors of the images using a palette
+    pub palette_size: usize,
+    // Chain the arms from the longest to the shortest, instead of in the order of the waves
+    pub sort_by_radius: bool,
+    // Draw the circle each arm turns along
+    pub show_circles: bool,
+    // Arms shorter than this, in pixels, are not drawn
+    pub min_arm_radius: f32,
+    // Mark the end of the chain, where the curve is drawn
+    pub show_tip: bool,
+}
+
+impl RenderOptions {
+    pub fn new(viewport: Viewport) -> RenderOptions {
+        RenderOptions {
+            viewport,
+            theme: Theme::light(),
+            period: 8.0,
+            trail_length: 1.0,
+            wave_counts: Vec::new(),
+            show_original: false,
+            frames: 60,
+            antialias: true,
+            fps: 30.0,
+            duration: None,
+            revolutions: 1.0,
+            palette_size: 256,
+            sort_by_radius: false,
+            show_circles: false,
+            min_arm_radius: 0.0,
+            show_tip: false,
+        }
+    }
+
+    // The animations cannot be timed without a positive period, frame rate and frame count,
+    // checked by every visualizer drawing them
+    pub fn validate(&self) -> Result<(), Error> {
+        self.viewport.validate()?;
+        if !(self.period > 0.0 && self.period.is_finite()) {
+            return Err(Error::InvalidTiming(format!("period {}", self.period)));
+        }
+        if !(self.fps > 0.0 && self.fps.is_finite()) {
+            return Err(Error::InvalidTiming(format!("frame rate {}", self.fps)));
+        }
+        if self.frames == 0 {
+            return Err(Error::InvalidTiming("frame count 0".to_string()));
+        }
+        if let Some(duration) = self.duration {
+            if !(duration > 0.0 && duration.is_finite()) {
+                return Err(Error::InvalidTiming(format!("duration {}", duration)));
+            }
+        }
+        if !(self.revolutions > 0.0 && self.revolutions.is_finite()) {
+            return Err(Error::InvalidTiming(format!("revolution count {}", self.revolutions)));
+        }
+        if !(64..=256).contains(&self.palette_size) {
+            return Err(Error::InvalidPaletteSize(self.palette_size.to_string()));
+        }
+        Ok(())
+    }
+
+    // Number of frames of the videos
+    pub fn video_frames(&self) -> usize {
+        let duration = self.duration.unwrap_or(self.revolutions * self.period);
+        (duration * self.fps).round() as usize
+    }
+
+    // Time of a video frame, counted in revolutions
+    pub fn video_frame_time(&self, frame: usize) -> f32 {
+        frame as f32 / self.fps / self.period
+    }
+}
+
+// The waves in the order their arms are chained, the first one not rotating stays first
+pub(crate) fn chained_waves(waves: &[DrawData], options: &RenderOptions) -> Vec<DrawData> {
+    let mut waves = waves.to_vec();
+    if options.sort_by_radius && waves.len() > 2 {
+        waves[1..].sort_by(|a, b| b.radius.total_cmp(&a.radius));
+    }
+    waves
+}
+
+// Closed curve drawn by the waves along a revolution, with enough points for its highest frequency
+pub(crate) fn trace(waves: &[DrawData], transform: &Transform) -> Vec<Point> {
+    let max_frequency = waves.iter().map(|d| d.frequency.abs() as usize).max().unwrap_or(0);
+    let mut points: Vec<Point> = fft_drawer::sample_series(waves, (max_frequency * 8).clamp(512, 8192))
+        .iter()
+        .map(|p| transform.transform_point(*p))
+        .collect();
+    points.push(points[0]);
+    points
+}
+
+// A single arm of 8 turning around the center of a 20 wide square, for the tests of the visualizers
+#[cfg(test)]
+pub(crate) fn test_drawings() -> Vec<fft_drawer::Drawing> {
+    vec![fft_drawer::Drawing::new(vec![DrawData::new(0.0, 0.0, 0.0), DrawData::new(1.0, 8.0, 0.0)], None)]
+}
+
+#[cfg(test)]
+pub(crate) fn test_options(width: f32, height: f32) -> RenderOptions {
+    RenderOptions::new(Viewport::new(lyon_path::math::rect(-10.0, -10.0, 20.0, 20.0), width, height, 0.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lyon_path::math::rect;
+
+    #[test]
+    fn writes_into_memory() {
+        let output = Output::memory();
+        output.write(b"first").unwrap();
+        output.clone().write(b"second").unwrap();
+        assert_eq!(output.contents().unwrap(), b"second");
+        assert_eq!(Output::Stdout.contents(), None);
+    }
+
+    #[test]
+    fn renders_from_other_threads() {
+        let output = Output::memory();
+        let visualizer = csv_visualizer::CsvVisualizer::new(output.clone());
+        std::thread::spawn(move || visualizer.render(&test_drawings(), &test_options(20.0, 20.0)))
+            .join()
+            .unwrap()
+            .unwrap();
+        assert!(!output.contents().unwrap().is_empty());
+    }
+
+    #[test]
+    fn rejects_animations_which_cannot_be_timed() {
+        let mut options = RenderOptions::new(Viewport::new(rect(0.0, 0.0, 10.0, 10.0), 100.0, 100.0, 0.0));
+        assert!(options.validate().is_ok());
+        for period in [0.0, -1.0, f32::NAN, f32::INFINITY] {
+            options.period = period;
+            assert!(matches!(options.validate(), Err(Error::InvalidTiming(_))));
+        }
+        options.period = 8.0;
+        options.fps = 0.0;
+        assert!(matches!(options.validate(), Err(Error::InvalidTiming(_))));
+        options.fps = 30.0;
+        options.frames = 0;
+        assert!(matches!(options.validate(), Err(Error::InvalidTiming(_))));
+        options.frames = 60;
+        options.duration = Some(-1.0);
+        assert!(matches!(options.validate(), Err(Error::InvalidTiming(_))));
+        options.duration = None;
+        options.revolutions = 0.0;
+        assert!(matches!(options.validate(), Err(Error::InvalidTiming(_))));
+        options.revolutions = 1.0;
+        options.palette_size = 16;
+        assert!(matches!(options.validate(), Err(Error::InvalidPaletteSize(_))));
+    }
 }
@@ -0,0 +1,79 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::Error;
+use crate::visualizer::{Visualizer, RenderOptions, Output};
+use crate::visualizer::raster::FrameRasterizer;
+use crate::fft_drawer::Drawing;
+
+// A numbered PNG image per frame, frames.png giving frames_0000.png, frames_0001.png...
+pub struct PngVisualizer {
+    output: Output,
+}
+
+impl PngVisualizer {
+    pub fn new(output: Output) -> PngVisualizer {
+        PngVisualizer{
+            output,
+        }
+    }
+}
+
+impl Visualizer for PngVisualizer {
+    fn render(&self, drawings: &[Drawing], options: &RenderOptions) -> Result<(), Error> {
+        options.validate()?;
+        let path = match &self.output {
+            Output::File(path) => path,
+            Output::Stdout | Output::Memory(_) => {
+                return Err(Error::UnsupportedOutput("PNG frames can only be written to files".to_string()));
+            }
+        };
+
+        let rasterizer = FrameRasterizer::new(drawings, options);
+        for i in 0..options.frames {
+            let pixmap = rasterizer.render(i as f32 / options.frames as f32)?;
+            let content = pixmap.encode_png().map_err(|err| Error::Render(err.to_string()))?;
+            let frame = frame_path(path, i);
+            fs::write(&frame, content).map_err(|err| Error::io(&frame, err))?;
+        }
+        Ok(())
+    }
+}
+
+fn frame_path(path: &Path, index: usize) -> PathBuf {
+    let stem = path.file_stem().and_then(|stem| stem.to_str()).unwrap_or("frame");
+    path.with_file_name(format!("{}_{:04}.png", stem, index))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::visualizer::{test_drawings, test_options};
+
+    #[test]
+    fn numbers_the_frames() {
+        assert_eq!(frame_path(Path::new("out/frames.png"), 12), PathBuf::from("out/frames_0012.png"));
+    }
+
+    #[test]
+    fn writes_a_file_per_frame() {
+        // The frames are files, in a directory of their own for each test run
+        let directory = std::env::temp_dir().join(format!("fourier_svg_png_visualizer_test_{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let visualizer = PngVisualizer::new(Output::File(directory.join("frames.png")));
+        let mut options = test_options(32.0, 24.0);
+        options.frames = 3;
+        let drawings = test_drawings();
+        visualizer.render(&drawings, &options).unwrap();
+
+        let mut names: Vec<_> = fs::read_dir(&directory).unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        names.sort();
+        fs::remove_dir_all(&directory).unwrap();
+        assert_eq!(names, vec!["frames_0000.png", "frames_0001.png", "frames_0002.png"]);
+
+        let visualizer = PngVisualizer::new(Output::memory());
+        assert!(matches!(visualizer.render(&drawings, &options), Err(Error::UnsupportedOutput(_))));
+    }
+}
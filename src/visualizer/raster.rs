@@ -0,0 +1,237 @@
+use lyon_path::math::{Point, Transform};
+use lyon_path::{Path, PathEvent};
+use tiny_skia::{FillRule, Paint, PathBuilder, Pixmap, Stroke, LineCap};
+
+use crate::error::Error;
+use crate::fft_drawer::{self, Drawing};
+use crate::visualizer::{RenderOptions, Color, chained_waves};
+
+// The trail fades in steps, each one stroked at once
+const TRAIL_STEPS: usize = 64;
+
+// Draws the animation frames into images, with the look of the HTML page
+pub struct FrameRasterizer<'a> {
+    drawings: &'a [Drawing],
+    options: &'a RenderOptions,
+    transform: Transform,
+}
+
+impl<'a> FrameRasterizer<'a> {
+    pub fn new(drawings: &'a [Drawing], options: &'a RenderOptions) -> FrameRasterizer<'a> {
+        FrameRasterizer {
+            drawings,
+            options,
+            transform: options.viewport.transform(),
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.options.viewport.width.round() as u32
+    }
+
+    pub fn height(&self) -> u32 {
+        self.options.viewport.height.round() as u32
+    }
+
+    // The frame at t, counted in revolutions
+    pub fn render(&self, t: f32) -> Result<Pixmap, Error> {
+        let mut pixmap = Pixmap::new(self.width(), self.height())
+            .ok_or_else(|| Error::Render(format!("invalid image size {}x{}", self.width(), self.height())))?;
+        pixmap.fill(skia_color(self.options.theme.background));
+
+        if self.options.show_original {
+            for original in self.drawings.iter().filter_map(|d| d.original.as_ref()) {
+                self.draw_original(&mut pixmap, original);
+            }
+        }
+        for drawing in self.drawings {
+            if drawing.waves.is_empty() {
+                continue;
+            }
+            self.draw_trail(&mut pixmap, drawing, t);
+            self.draw_arms(&mut pixmap, drawing, t);
+        }
+        Ok(pixmap)
+    }
+
+    fn draw_arms(&self, pixmap: &mut Pixmap, drawing: &Drawing, t: f32) {
+        // The first wave does not rotate, the arms start at its end
+        let centers: Vec<Point> = fft_drawer::epicycle_centers(&chained_waves(&drawing.waves, self.options), t)
+            .iter()
+            .map(|p| self.transform.transform_point(*p))
+            .collect();
+        let mut arms = PathBuilder::new();
+        let mut circles = PathBuilder::new();
+        for arm in centers[1..].windows(2) {
+            let radius = (arm[1] - arm[0]).length();
+            if radius < self.options.min_arm_radius {
+                continue;
+            }
+            arms.move_to(arm[0].x, arm[0].y);
+            arms.line_to(arm[1].x, arm[1].y);
+            if self.options.show_circles {
+                circles.push_circle(arm[0].x, arm[0].y, radius);
+            }
+        }
+        let theme = &self.options.theme;
+        self.stroke(pixmap, circles, theme.circle_color, theme.arm_width);
+        self.stroke(pixmap, arms, theme.arm_color, theme.arm_width);
+
+        if self.options.show_tip {
+            let tip = centers[centers.len() - 1];
+            if let Some(marker) = PathBuilder::from_circle(tip.x, tip.y, (2.0 * theme.arm_width).max(3.0)) {
+                let mut paint = Paint::default();
+                paint.set_color(skia_color(theme.trail_color));
+                paint.anti_alias = self.options.antialias;
+                pixmap.fill_path(&marker, &paint, FillRule::Winding, tiny_skia::Transform::identity(), None);
+            }
+        }
+    }
+
+    // Mapped onto the image like the waves, under everything else
+    fn draw_original(&self, pixmap: &mut Pixmap, original: &Path) {
+        let mut builder = PathBuilder::new();
+        for evt in original.iter() {
+            match evt {
+                PathEvent::Begin { at } => {
+                    let at = self.transform.transform_point(at);
+                    builder.move_to(at.x, at.y);
+                }
+                PathEvent::Line { to, .. } => {
+                    let to = self.transform.transform_point(to);
+                    builder.line_to(to.x, to.y);
+                }
+                PathEvent::Quadratic { ctrl, to, .. } => {
+                    let (ctrl, to) = (self.transform.transform_point(ctrl), self.transform.transform_point(to));
+                    builder.quad_to(ctrl.x, ctrl.y, to.x, to.y);
+                }
+                PathEvent::Cubic { ctrl1, ctrl2, to, .. } => {
+                    let (ctrl1, ctrl2, to) = (self.transform.transform_point(ctrl1), self.transform.transform_point(ctrl2), self.transform.transform_point(to));
+                    builder.cubic_to(ctrl1.x, ctrl1.y, ctrl2.x, ctrl2.y, to.x, to.y);
+                }
+                PathEvent::End { close: true, .. } => builder.close(),
+                PathEvent::End { .. } => {}
+            }
+        }
+        let theme = &self.options.theme;
+        self.stroke(pixmap, builder, theme.original_color, theme.trail_width);
+    }
+
+    fn draw_trail(&self, pixmap: &mut Pixmap, drawing: &Drawing, t: f32) {
+        let max_frequency = drawing.waves.iter().map(|d| d.frequency.abs() as usize).max().unwrap_or(0);
+        let n_point = ((max_frequency * 8).clamp(512, 8192) as f32 * self.options.trail_length) as usize;
+        if n_point < 2 {
+            return;
+        }
+
+        // From the newest point, going back in time
+        let points: Vec<Point> = (0..n_point)
+            .map(|i| {
+                let time = t - self.options.trail_length * i as f32 / (n_point - 1) as f32;
+                self.transform.transform_point(fft_drawer::series_point(&drawing.waves, time))
+            })
+            .collect();
+
+        // Older parts of the trail are more transparent
+        let step_size = n_point.div_ceil(TRAIL_STEPS);
+        for (step, start) in (0..n_point - 1).step_by(step_size).enumerate() {
+            let end = (start + step_size).min(n_point - 1);
+            let mut builder = PathBuilder::new();
+            builder.move_to(points[start].x, points[start].y);
+            for p in &points[start + 1..=end] {
+                builder.line_to(p.x, p.y);
+            }
+            let theme = &self.options.theme;
+            let color = theme.trail_color_at(step as f32 * step_size as f32 / n_point as f32);
+            self.stroke(pixmap, builder, color, theme.trail_width);
+        }
+    }
+
+    fn stroke(&self, pixmap: &mut Pixmap, builder: PathBuilder, color: Color, width: f32) {
+        let path = match builder.finish() {
+            Some(path) => path,
+            None => return,
+        };
+        let mut paint = Paint::default();
+        paint.set_color(skia_color(color));
+        paint.anti_alias = self.options.antialias;
+        let stroke = Stroke {
+            width,
+            line_cap: LineCap::Round,
+            ..Stroke::default()
+        };
+        pixmap.stroke_path(&path, &paint, &stroke, tiny_skia::Transform::identity(), None);
+    }
+}
+
+fn skia_color(color: Color) -> tiny_skia::Color {
+    tiny_skia::Color::from_rgba8(color.r, color.g, color.b, (color.a.clamp(0.0, 1.0) * 255.0).round() as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::visualizer::{test_drawings, test_options};
+    use crate::fft_drawer::DrawData;
+    use crate::path_util::build_path_from_svg;
+    use crate::viewport::Viewport;
+    use lyon_path::math::rect;
+
+    #[test]
+    fn draws_the_arms_and_the_trail() {
+        let options = test_options(40.0, 40.0);
+        let drawings = test_drawings();
+        let rasterizer = FrameRasterizer::new(&drawings, &options);
+        let pixmap = rasterizer.render(0.0).unwrap();
+        assert_eq!((pixmap.width(), pixmap.height()), (40, 40));
+
+        // The background is kept in the corners, the arm goes right from the center
+        assert_eq!(pixmap.pixel(0, 0).unwrap().red(), 255);
+        let arm = pixmap.pixel(28, 20).unwrap();
+        assert!(arm.green() < 255);
+        // The trail ends at the tip of the arm
+        let trail = pixmap.pixel(36, 19).unwrap();
+        assert!(trail.red() < 255);
+    }
+
+    #[test]
+    fn draws_the_circles_and_the_tip() {
+        let mut options = test_options(40.0, 40.0);
+        options.show_circles = true;
+        options.show_tip = true;
+        options.trail_length = 0.0;
+        let drawings = test_drawings();
+        let pixmap = FrameRasterizer::new(&drawings, &options).render(0.0).unwrap();
+        // Across the circle from the arm, then on the marker
+        assert!(pixmap.pixel(4, 20).unwrap().green() < 255);
+        assert!(pixmap.pixel(37, 22).unwrap().red() < 255);
+
+        // Too short arms are left out, with their circle
+        options.min_arm_radius = 20.0;
+        let pixmap = FrameRasterizer::new(&drawings, &options).render(0.0).unwrap();
+        assert_eq!(pixmap.pixel(4, 20).unwrap().green(), 255);
+        assert_eq!(pixmap.pixel(28, 20).unwrap().green(), 255);
+    }
+
+    #[test]
+    fn draws_the_original_path_under_the_waves() {
+        let mut options = test_options(40.0, 40.0);
+        options.trail_length = 0.0;
+        let original = build_path_from_svg("M -8 -8 L 8 -8 L 8 8 L -8 8 Z").unwrap();
+        let drawings = vec![Drawing::new(vec![DrawData::new(0.0, 0.0, 0.0)], Some(original))];
+        // Only drawn when asked, on the same spot as the waves would be
+        let pixmap = FrameRasterizer::new(&drawings, &options).render(0.0).unwrap();
+        assert_eq!(pixmap.pixel(20, 4).unwrap().red(), 255);
+        options.show_original = true;
+        let pixmap = FrameRasterizer::new(&drawings, &options).render(0.0).unwrap();
+        assert!(pixmap.pixel(20, 4).unwrap().red() < 255);
+        assert!(pixmap.pixel(36, 20).unwrap().red() < 255);
+        assert_eq!(pixmap.pixel(20, 20).unwrap().red(), 255);
+    }
+
+    #[test]
+    fn rejects_empty_images() {
+        let options = RenderOptions::new(Viewport::new(rect(0.0, 0.0, 1.0, 1.0), 0.0, 10.0, 0.0));
+        assert!(matches!(FrameRasterizer::new(&[], &options).render(0.0), Err(Error::Render(_))));
+    }
+}
@@ -0,0 +1,106 @@
+use crate::visualizer::{Visualizer, Output};
+use crate::visualizer::html_visualizer::HTMLVisualizer;
+use crate::visualizer::svg_visualizer::SvgVisualizer;
+use crate::visualizer::static_svg_visualizer::StaticSvgVisualizer;
+use crate::visualizer::png_visualizer::PngVisualizer;
+use crate::visualizer::gif_visualizer::GifVisualizer;
+use crate::visualizer::y4m_visualizer::Y4mVisualizer;
+use crate::visualizer::json_visualizer::JsonVisualizer;
+use crate::visualizer::csv_visualizer::CsvVisualizer;
+
+pub struct VisualizerEntry {
+    pub name: &'static str,
+    // Used for the default output file name
+    pub extension: &'static str,
+    // Shown in the help of --visualizer
+    pub description: &'static str,
+    pub create: fn(Output) -> Box<dyn Visualizer>,
+}
+
+// Every available visualizer, the first one is the default
+pub const VISUALIZERS: &[VisualizerEntry] = &[
+    VisualizerEntry {
+        name: "html",
+        extension: "html",
+        description: "Animated web page drawing on a canvas",
+        create: |output| Box::new(HTMLVisualizer::new(output)),
+    },
+    VisualizerEntry {
+        name: "svg",
+        extension: "svg",
+        description: "Animated SVG image, for documents where scripts cannot run",
+        create: |output| Box::new(SvgVisualizer::new(output)),
+    },
+    VisualizerEntry {
+        name: "svg-static",
+        extension: "svg",
+        description: "Still SVG image of the whole curve, comparing wave counts",
+        create: |output| Box::new(StaticSvgVisualizer::new(output)),
+    },
+    VisualizerEntry {
+        name: "png",
+        extension: "png",
+        description: "Numbered PNG image per frame of a revolution",
+        create: |output| Box::new(PngVisualizer::new(output)),
+    },
+    VisualizerEntry {
+        name: "gif",
+        extension: "gif",
+        description: "Looping animated GIF image",
+        create: |output| Box::new(GifVisualizer::new(output)),
+    },
+    VisualizerEntry {
+        name: "y4m",
+        extension: "y4m",
+        description: "Uncompressed YUV4MPEG2 video, to pipe into an encoder",
+        create: |output| Box::new(Y4mVisualizer::new(output)),
+    },
+    VisualizerEntry {
+        name: "json",
+        extension: "json",
+        description: "Coefficient file, to draw the waves again with --coefficients",
+        create: |output| Box::new(JsonVisualizer::new(output)),
+    },
+    VisualizerEntry {
+        name: "csv",
+        extension: "csv",
+        description: "Table of the waves, a row per wave",
+        create: |output| Box::new(CsvVisualizer::new(output)),
+    },
+];
+
+pub fn find_visualizer(name: &str) -> Option<&'static VisualizerEntry> {
+    VISUALIZERS.iter().find(|entry| entry.name == name)
+}
+
+pub fn visualizer_names() -> Vec<&'static str> {
+    VISUALIZERS.iter().map(|entry| entry.name).collect()
+}
+
+// A line per visualizer, its name followed by its description
+pub fn visualizer_descriptions() -> String {
+    let width = VISUALIZERS.iter().map(|entry| entry.name.len()).max().unwrap_or(0);
+    VISUALIZERS.iter()
+        .map(|entry| format!("{:width$}  {}", entry.name, entry.description, width = width))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_visualizers_by_name() {
+        assert_eq!(find_visualizer("html").unwrap().extension, "html");
+        assert!(find_visualizer("unknown").is_none());
+        assert_eq!(visualizer_names()[0], VISUALIZERS[0].name);
+    }
+
+    #[test]
+    fn describes_every_visualizer() {
+        let descriptions = visualizer_descriptions();
+        assert_eq!(descriptions.lines().count(), VISUALIZERS.len());
+        assert!(descriptions.lines().next().unwrap().starts_with("html        Animated web page"));
+    }
+}
@@ -0,0 +1,129 @@
+use std::fmt::Write;
+
+use crate::error::Error;
+use crate::visualizer::{Visualizer, RenderOptions, Output, Color, trace};
+use crate::visualizer::svg_visualizer::svg_color;
+use crate::fft_drawer::Drawing;
+use crate::path_util::path_to_svg;
+
+// Told apart when comparing several wave counts
+const LAYER_COLORS: &[Color] = &[
+    Color { r: 214, g: 39, b: 40, a: 1.0 },
+    Color { r: 31, g: 119, b: 180, a: 1.0 },
+    Color { r: 44, g: 160, b: 44, a: 1.0 },
+    Color { r: 255, g: 127, b: 14, a: 1.0 },
+    Color { r: 148, g: 103, b: 189, a: 1.0 },
+    Color { r: 23, g: 190, b: 207, a: 1.0 },
+];
+
+// The curve drawn after a whole revolution, as a still SVG image
+pub struct StaticSvgVisualizer {
+    output: Output,
+}
+
+impl StaticSvgVisualizer {
+    pub fn new(output: Output) -> StaticSvgVisualizer {
+        StaticSvgVisualizer{
+            output,
+        }
+    }
+}
+
+impl Visualizer for StaticSvgVisualizer {
+    fn render(&self, drawings: &[Drawing], options: &RenderOptions) -> Result<(), Error> {
+        let viewport = &options.viewport;
+        let transform = viewport.transform();
+
+        // Without wave counts, draw with all the waves
+        let max_wave = drawings.iter().map(|d| d.waves.len()).max().unwrap_or(0);
+        let mut wave_counts: Vec<usize> = Vec::new();
+        if options.wave_counts.is_empty() {
+            wave_counts.push(max_wave);
+        }
+        // Counts above the number of waves all draw the full curve, kept once for unique ids
+        for count in options.wave_counts.iter().map(|&count| count.min(max_wave)) {
+            if !wave_counts.contains(&count) {
+                wave_counts.push(count);
+            }
+        }
+
+        let mut content = String::new();
+        writeln!(content, "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">",
+            w = viewport.width, h = viewport.height).unwrap();
+        writeln!(content, "<rect width=\"100%\" height=\"100%\" {}/>", svg_color(options.theme.background, "fill")).unwrap();
+
+        if options.show_original {
+            writeln!(content, "<g id=\"original\" fill=\"none\" {} stroke-width=\"{}\">",
+                svg_color(options.theme.original_color, "stroke"), options.theme.trail_width).unwrap();
+            for original in drawings.iter().filter_map(|d| d.original.as_ref()) {
+                writeln!(content, "<path d=\"{}\"/>", path_to_svg(&original.clone().transformed(&transform))).unwrap();
+            }
+            writeln!(content, "</g>").unwrap();
+        }
+
+        for (i, &count) in wave_counts.iter().enumerate() {
+            let color = if wave_counts.len() == 1 {
+                options.theme.trail_color
+            } else {
+                LAYER_COLORS[i % LAYER_COLORS.len()]
+            };
+            writeln!(content, "<g id=\"waves-{}\" fill=\"none\" {} stroke-width=\"{}\">",
+                count, svg_color(color, "stroke"), options.theme.trail_width).unwrap();
+            writeln!(content, "<title>{} waves</title>", count).unwrap();
+            for drawing in drawings {
+                let waves = &drawing.waves[..count.min(drawing.waves.len())];
+                if waves.is_empty() {
+                    continue;
+                }
+                let mut path_data = String::new();
+                for (j, p) in trace(waves, &transform).iter().enumerate() {
+                    write!(path_data, "{}{:.2} {:.2} ", if j == 0 { "M" } else { "L" }, p.x, p.y).unwrap();
+                }
+                writeln!(content, "<path d=\"{}Z\"/>", path_data).unwrap();
+            }
+            writeln!(content, "</g>").unwrap();
+        }
+
+        // Tell which color is which wave count
+        if wave_counts.len() > 1 {
+            writeln!(content, "<g font-family=\"sans-serif\" font-size=\"12\">").unwrap();
+            for (i, count) in wave_counts.iter().enumerate() {
+                writeln!(content, "<text x=\"10\" y=\"{}\" {}>{} waves</text>",
+                    20 + 16 * i, svg_color(LAYER_COLORS[i % LAYER_COLORS.len()], "fill"), count).unwrap();
+            }
+            writeln!(content, "</g>").unwrap();
+        }
+        content.push_str("</svg>\n");
+
+        self.output.write(content.as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fft_drawer::DrawData;
+    use crate::path_util::build_path_from_svg;
+    use crate::viewport::Viewport;
+    use lyon_path::math::rect;
+
+    #[test]
+    fn draws_a_layer_per_wave_count() {
+        let output = Output::memory();
+        let visualizer = StaticSvgVisualizer::new(output.clone());
+        let mut options = RenderOptions::new(Viewport::new(rect(0.0, 0.0, 10.0, 10.0), 100.0, 100.0, 0.0));
+        options.wave_counts = vec![1, 101, 3, 201];
+        options.show_original = true;
+        let waves = vec![DrawData::new(0.0, 5.0, 0.0), DrawData::new(1.0, 2.0, 0.0), DrawData::new(-1.0, 1.0, 0.0)];
+        let original = build_path_from_svg("M 0 0 L 10 0 L 10 10 Z").unwrap();
+        visualizer.render(&[Drawing::new(waves, Some(original))], &options).unwrap();
+
+        let content = String::from_utf8(output.contents().unwrap()).unwrap();
+        assert_eq!(content.matches("<g id=\"waves-1\"").count(), 1);
+        // There are not that many waves, so 101 and 201 draw the layer of 3 waves
+        assert_eq!(content.matches("<g id=\"waves-3\"").count(), 1);
+        assert_eq!(content.matches("<g id=").count(), 3);
+        assert_eq!(content.matches(">3 waves</text>").count(), 1);
+        assert!(content.contains("<path d=\"M0 0 L100 0 L100 100 Z\"/>"));
+    }
+}
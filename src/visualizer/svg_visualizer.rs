@@ -0,0 +1,108 @@
+use std::f32::consts::PI;
+use std::fmt::Write;
+
+use crate::error::Error;
+use crate::visualizer::{Visualizer, RenderOptions, Output, Color, trace, chained_waves};
+use crate::fft_drawer::{self, DrawData, Drawing};
+
+// Self-contained animated SVG, using SMIL so it also plays where scripts cannot run
+pub struct SvgVisualizer {
+    output: Output,
+}
+
+impl SvgVisualizer {
+    pub fn new(output: Output) -> SvgVisualizer {
+        SvgVisualizer{
+            output,
+        }
+    }
+}
+
+impl Visualizer for SvgVisualizer {
+    fn render(&self, drawings: &[Drawing], options: &RenderOptions) -> Result<(), Error> {
+        options.validate()?;
+        let viewport = &options.viewport;
+        let transform = viewport.transform();
+        let scale = viewport.scale();
+        let period = options.period;
+
+        let mut content = String::new();
+        writeln!(content, "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">",
+            w = viewport.width, h = viewport.height).unwrap();
+        writeln!(content, "<rect width=\"100%\" height=\"100%\" {}/>", svg_color(options.theme.background, "fill")).unwrap();
+
+        for drawing in drawings {
+            let waves = &drawing.waves;
+            if waves.is_empty() {
+                continue;
+            }
+
+            // The curve, revealed along one revolution
+            let points = trace(waves, &transform);
+            let length: f32 = points.windows(2).map(|p| (p[1] - p[0]).length()).sum();
+            let mut path_data = String::new();
+            for (i, p) in points.iter().enumerate() {
+                write!(path_data, "{}{:.2} {:.2} ", if i == 0 { "M" } else { "L" }, p.x, p.y).unwrap();
+            }
+            writeln!(content, "<path d=\"{}\" fill=\"none\" {} stroke-width=\"{}\" stroke-dasharray=\"{len:.2}\" stroke-dashoffset=\"{len:.2}\">",
+                path_data.trim_end(), svg_color(options.theme.trail_color, "stroke"), options.theme.trail_width, len = length).unwrap();
+            writeln!(content, "<animate attributeName=\"stroke-dashoffset\" from=\"{:.2}\" to=\"0\" dur=\"{}s\" repeatCount=\"indefinite\"/>",
+                length, period).unwrap();
+            writeln!(content, "</path>").unwrap();
+
+            // The arms, each one rotating relatively to the previous one
+            let center = transform.transform_point(fft_drawer::epicycle_centers(&waves[..1], 0.0)[1]);
+            writeln!(content, "<g transform=\"translate({:.2} {:.2})\" {} stroke-width=\"{}\" stroke-linecap=\"round\">",
+                center.x, center.y, svg_color(options.theme.arm_color, "stroke"), options.theme.arm_width).unwrap();
+            let mut previous = DrawData::new(0.0, 0.0, 0.0);
+            for d in &chained_waves(waves, options)[1..] {
+                let from = (d.angle - previous.angle) * 180.0 / PI;
+                let to = from + 360.0 * (d.frequency - previous.frequency);
+                writeln!(content, "<g>").unwrap();
+                writeln!(content, "<animateTransform attributeName=\"transform\" type=\"rotate\" from=\"{:.3}\" to=\"{:.3}\" dur=\"{}s\" repeatCount=\"indefinite\"/>",
+                    from, to, period).unwrap();
+                writeln!(content, "<line x2=\"{:.3}\"/>", d.radius * scale).unwrap();
+                writeln!(content, "<g transform=\"translate({:.3} 0)\">", d.radius * scale).unwrap();
+                previous = d.clone();
+            }
+            for _ in 1..waves.len() {
+                content.push_str("</g></g>\n");
+            }
+            writeln!(content, "</g>").unwrap();
+        }
+        content.push_str("</svg>\n");
+
+        self.output.write(content.as_bytes())
+    }
+}
+
+// SVG 1.1 has no alpha in colors
+pub(crate) fn svg_color(color: Color, property: &str) -> String {
+    format!("{p}=\"rgb({}, {}, {})\" {p}-opacity=\"{}\"", color.r, color.g, color.b, color.a, p = property)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::visualizer::test_options;
+
+    #[test]
+    fn nests_a_rotating_group_per_arm() {
+        let output = Output::memory();
+        let visualizer = SvgVisualizer::new(output.clone());
+        let options = test_options(100.0, 100.0);
+        let drawings = vec![Drawing::new(vec![
+            DrawData::new(0.0, 0.0, 0.0),
+            DrawData::new(1.0, 5.0, 0.0),
+            DrawData::new(-1.0, 2.0, PI / 2.0),
+        ], None)];
+        visualizer.render(&drawings, &options).unwrap();
+
+        let content = String::from_utf8(output.contents().unwrap()).unwrap();
+        assert_eq!(content.matches("<animateTransform").count(), 2);
+        // The second arm turns back twice as fast as the first one turns forward
+        assert!(content.contains("from=\"90.000\" to=\"-630.000\""));
+        assert!(content.contains("<line x2=\"25.000\"/>"));
+        assert_eq!(content.matches("<g").count(), content.matches("</g>").count());
+    }
+}
@@ -0,0 +1,116 @@
+use std::fs;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::Error;
+use crate::visualizer::Color;
+
+// How the drawings look, read from a JSON file where the missing fields keep the light look:
+// { "background": "#101018", "trail_color": "rgba(120, 220, 255, 1)", "trail_width": 2 }
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Theme {
+    pub background: Color,
+    pub arm_color: Color,
+    pub circle_color: Color,
+    // Color of the newest end of the trail
+    pub trail_color: Color,
+    // Color of the oldest end of the trail, the trail color faded out when not set
+    pub trail_end_color: Option<Color>,
+    pub original_color: Color,
+    // Width of the arms and their circles
+    pub arm_width: f32,
+    // Width of the curves, the trail and the original path
+    pub trail_width: f32,
+}
+
+impl Theme {
+    pub fn light() -> Theme {
+        Theme {
+            background: Color::rgba(255, 255, 255, 1.0),
+            arm_color: Color::rgba(202, 126, 86, 0.7),
+            circle_color: Color::rgba(128, 128, 128, 0.3),
+            trail_color: Color::rgba(0, 0, 0, 1.0),
+            trail_end_color: None,
+            original_color: Color::rgba(128, 128, 128, 0.5),
+            arm_width: 1.0,
+            trail_width: 1.0,
+        }
+    }
+
+    pub fn dark() -> Theme {
+        Theme {
+            background: Color::rgba(16, 16, 24, 1.0),
+            arm_color: Color::rgba(240, 160, 110, 0.8),
+            circle_color: Color::rgba(200, 200, 220, 0.25),
+            trail_color: Color::rgba(120, 220, 255, 1.0),
+            trail_end_color: Some(Color::rgba(170, 90, 255, 0.0)),
+            original_color: Color::rgba(200, 200, 200, 0.35),
+            arm_width: 1.0,
+            trail_width: 1.5,
+        }
+    }
+
+    pub fn preset(name: &str) -> Option<Theme> {
+        match name {
+            "light" => Some(Theme::light()),
+            "dark" => Some(Theme::dark()),
+            _ => None,
+        }
+    }
+
+    pub fn from_json(content: &str) -> Result<Theme, Error> {
+        serde_json::from_str(content).map_err(|err| Error::InvalidTheme(err.to_string()))
+    }
+
+    // Color of the trail drawn age revolutions ago, from 0 to 1 along the trail
+    pub fn trail_color_at(&self, age: f32) -> Color {
+        let start = self.trail_color;
+        let end = self.trail_end_color.unwrap_or(Color { a: 0.0, ..start });
+        let mix = |from: u8, to: u8| (from as f32 + (to as f32 - from as f32) * age).round() as u8;
+        Color::rgba(mix(start.r, end.r), mix(start.g, end.g), mix(start.b, end.b), start.a + (end.a - start.a) * age)
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Theme {
+        Theme::light()
+    }
+}
+
+// A preset name, or a theme file
+pub fn read_theme(name: &str) -> Result<Theme, Error> {
+    match Theme::preset(name) {
+        Some(theme) => Ok(theme),
+        None => {
+            let content = fs::read_to_string(name).map_err(|err| Error::io(name, err))?;
+            Theme::from_json(&content)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_a_partial_theme() {
+        let theme = Theme::from_json("{\"background\": \"#101018\", \"trail_end_color\": \"rgba(255, 0, 0, 0.5)\", \"trail_width\": 2}").unwrap();
+        assert_eq!(theme.background, Color::rgba(16, 16, 24, 1.0));
+        assert_eq!(theme.trail_width, 2.0);
+        assert_eq!(theme.arm_color, Theme::light().arm_color);
+        assert_eq!(theme.trail_color_at(0.5), Color::rgba(128, 0, 0, 0.75));
+
+        assert_eq!("red".parse::<Color>().unwrap(), Color::rgba(255, 0, 0, 1.0));
+        assert_eq!("#00ff0080".parse::<Color>().unwrap(), Color::rgba(0, 255, 0, 128.0 / 255.0));
+        assert!(matches!(Theme::from_json("{\"background\": \"nope\"}"), Err(Error::InvalidTheme(_))));
+        assert!(matches!(Theme::from_json("{\"arms\": \"red\"}"), Err(Error::InvalidTheme(_))));
+    }
+
+    #[test]
+    fn fades_the_trail_out_by_default() {
+        let theme = Theme::light();
+        assert_eq!(theme.trail_color_at(0.0), theme.trail_color);
+        assert_eq!(theme.trail_color_at(0.75), Color::rgba(0, 0, 0, 0.25));
+    }
+}
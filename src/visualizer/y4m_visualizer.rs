@@ -0,0 +1,100 @@
+use crate::error::Error;
+use crate::visualizer::{Visualizer, RenderOptions, Output};
+use crate::visualizer::raster::FrameRasterizer;
+use crate::fft_drawer::Drawing;
+
+// Uncompressed YUV4MPEG2 video, big but understood by video encoders such as ffmpeg
+pub struct Y4mVisualizer {
+    output: Output,
+}
+
+impl Y4mVisualizer {
+    pub fn new(output: Output) -> Y4mVisualizer {
+        Y4mVisualizer{
+            output,
+        }
+    }
+}
+
+impl Visualizer for Y4mVisualizer {
+    fn render(&self, drawings: &[Drawing], options: &RenderOptions) -> Result<(), Error> {
+        options.validate()?;
+        let rasterizer = FrameRasterizer::new(drawings, options);
+        let (numerator, denominator) = frame_rate(options.fps);
+
+        let mut writer = self.output.writer()?;
+        writeln!(writer, "YUV4MPEG2 W{} H{} F{}:{} Ip A1:1 C444",
+            rasterizer.width(), rasterizer.height(), numerator, denominator)
+            .map_err(|err| self.output.io_error(err))?;
+        for i in 0..options.video_frames().max(1) {
+            let pixmap = rasterizer.render(options.video_frame_time(i))?;
+            writer.write_all(b"FRAME\n").map_err(|err| self.output.io_error(err))?;
+            writer.write_all(&planes(pixmap.data())).map_err(|err| self.output.io_error(err))?;
+        }
+        writer.flush().map_err(|err| self.output.io_error(err))?;
+        Ok(())
+    }
+}
+
+// Exact for whole and NTSC-like frame rates
+fn frame_rate(fps: f32) -> (u32, u32) {
+    if fps.fract() == 0.0 {
+        (fps as u32, 1)
+    } else {
+        ((fps * 1001.0).round() as u32, 1001)
+    }
+}
+
+// The Y, U and V planes of premultiplied RGBA pixels, as if drawn over black,
+// with the BT.601 studio range most players expect
+fn planes(data: &[u8]) -> Vec<u8> {
+    let pixel_count = data.len() / 4;
+    let mut planes = vec![0; pixel_count * 3];
+    for (i, pixel) in data.chunks_exact(4).enumerate() {
+        let (r, g, b) = (pixel[0] as f32, pixel[1] as f32, pixel[2] as f32);
+        planes[i] = (16.0 + 0.2568 * r + 0.5041 * g + 0.0979 * b).round() as u8;
+        planes[pixel_count + i] = (128.0 - 0.1482 * r - 0.2910 * g + 0.4392 * b).round() as u8;
+        planes[2 * pixel_count + i] = (128.0 + 0.4392 * r - 0.3678 * g - 0.0714 * b).round() as u8;
+    }
+    planes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::visualizer::{test_drawings, test_options};
+
+    #[test]
+    fn converts_to_studio_range() {
+        assert_eq!(planes(&[255, 255, 255, 255, 0, 0, 0, 255]), vec![235, 16, 128, 128, 128, 128]);
+        assert_eq!(frame_rate(29.97), (30000, 1001));
+    }
+
+    #[test]
+    fn writes_the_frames_of_the_revolutions() {
+        let output = Output::memory();
+        let visualizer = Y4mVisualizer::new(output.clone());
+        let mut options = test_options(8.0, 6.0);
+        options.fps = 12.0;
+        options.period = 1.0;
+        options.revolutions = 2.0;
+        let drawings = test_drawings();
+        visualizer.render(&drawings, &options).unwrap();
+
+        let content = output.contents().unwrap();
+        let header = b"YUV4MPEG2 W8 H6 F12:1 Ip A1:1 C444\n";
+        assert!(content.starts_with(header));
+        // A revolution lasts a second
+        assert_eq!(content.len(), header.len() + 24 * (6 + 8 * 6 * 3));
+    }
+
+    #[test]
+    fn rejects_frame_rates_which_cannot_time_the_video() {
+        let output = Output::memory();
+        let mut options = test_options(8.0, 6.0);
+        options.fps = 0.0;
+        let result = Y4mVisualizer::new(output.clone()).render(&test_drawings(), &options);
+        assert!(matches!(result, Err(Error::InvalidTiming(_))));
+        assert!(output.contents().unwrap().is_empty());
+    }
+}